@@ -103,7 +103,6 @@ fn test_invalid_config_file() {
 }
 
 #[test]
-#[ignore = "TODO: Fix/implement html_escape"]
 fn test_html_escaping() {
     // Create a config that explicitly enables HTML escaping
     let config_content = r#"
@@ -1,3 +1,8 @@
+use crate::html::hooks::RenderHooks;
+use crate::html::state::HeadingIds;
+use crate::html::HtmlError;
+use crate::utils::sanitize_id;
+use pulldown_cmark::Options;
 use serde::Deserialize;
 use std::collections::HashMap;
 
@@ -12,11 +17,17 @@ pub struct HtmlConfig {
     pub attributes: AttributeMappings,
     /// Syntect syntax highlighting configuration (style only)
     pub syntect: Option<crate::html::syntect::SyntectConfigStyle>,
+    /// Rendering callbacks; these cannot be deserialized and are set in code
+    #[serde(skip)]
+    pub hooks: RenderHooks,
 }
 /// Configuration options for HTML output
 #[derive(Debug, Clone, Deserialize)]
 pub struct HtmlOptions {
     /// Whether to escape HTML in the input
+    ///
+    /// Raw HTML is written as text, keeping any character references it
+    /// contains, such as `&amp;`, as they are.
     pub escape_html: bool,
     /// Whether to convert newlines to <br> tags
     pub break_on_newline: bool,
@@ -24,6 +35,189 @@ pub struct HtmlOptions {
     pub xhtml_style: bool,
     /// Whether to add newlines after block elements for prettier output
     pub pretty_print: bool,
+    /// Quote character used around attribute values
+    #[serde(default)]
+    pub attribute_quote: AttrQuote,
+    /// How to handle parser events the renderer has no HTML mapping for
+    #[serde(default)]
+    pub unknown_event_policy: UnknownPolicy,
+    /// Whether to drop paragraphs that have no content
+    #[serde(default)]
+    pub drop_empty_paragraphs: bool,
+    /// Whether to turn bare `http(s)://` URLs in text into links
+    #[serde(default)]
+    pub autolink_bare_urls: bool,
+    /// Line ending used for newlines in the output
+    #[serde(default)]
+    pub line_ending: LineEnding,
+    /// Whether to add schema.org Article microdata to the document
+    #[serde(default)]
+    pub schema_org: bool,
+    /// Whether to render pandoc-style `^sup^` and `~sub~` text as `<sup>` and `<sub>`
+    #[serde(default)]
+    pub pandoc_sup_sub: bool,
+    /// Whether to fail rendering when an end tag does not match the most
+    /// recently started tag, instead of trusting the event stream
+    #[serde(default)]
+    pub strict_nesting: bool,
+    /// Whether to wrap ISO dates like `2024-01-15` in text in `<time>` elements
+    #[serde(default)]
+    pub detect_dates: bool,
+    /// Attributes added to every block element: `p`, `h1`-`h6`, `ul`, `ol`,
+    /// `li`, `blockquote`, `pre` and `table`
+    #[serde(default)]
+    pub global_block_attributes: HashMap<String, String>,
+    /// HTML written between documents by [`HtmlRenderer::run_with_separator`](crate::HtmlRenderer::run_with_separator)
+    #[serde(default = "default_document_separator")]
+    pub document_separator: String,
+    /// Whether to follow each `<br>` with a newline, keeping the output diff-friendly
+    #[serde(default)]
+    pub break_with_newline: bool,
+    /// How to render emoji shortcodes like `:smile:` in text
+    #[serde(default)]
+    pub emoji: EmojiMode,
+    /// Whether to trim trailing spaces and tabs from the lines of text outside code
+    ///
+    /// Whitespace before a hard break is kept.
+    #[serde(default)]
+    pub trim_trailing_whitespace: bool,
+    /// Maximum nesting of emphasis and strong emphasis, beyond which the
+    /// markers are rendered as literal text
+    #[serde(default)]
+    pub max_inline_nesting: Option<usize>,
+    /// HTML comment marking the end of the excerpt rendered by `render_excerpt`
+    #[serde(default = "default_excerpt_marker")]
+    pub excerpt_marker: String,
+    /// Whether to match the output of pulldown-cmark's reference renderer
+    ///
+    /// Overrides the options where this crate diverges by default: soft breaks
+    /// stay newlines, hard breaks and void elements like `<hr />` use the XHTML
+    /// form followed by a newline, trailing whitespace is trimmed, headings
+    /// get no generated IDs, external links get no `rel` or `target` and text
+    /// is escaped. Raw HTML is passed through unless `escape_html` is set.
+    #[serde(default)]
+    pub commonmark_strict: bool,
+    /// Whether to write non-ASCII characters as numeric character references,
+    /// e.g. `&#233;`, for clients that mishandle other encodings
+    #[serde(default)]
+    pub encode_non_ascii: bool,
+    /// Whether `encode_non_ascii` also applies to the text of code blocks
+    #[serde(default)]
+    pub encode_non_ascii_in_code: bool,
+    /// Whether to emit AMP-compatible markup: images become `<amp-img>`, which
+    /// must have dimensions, and event handler and `style` attributes are dropped
+    #[serde(default)]
+    pub amp_mode: bool,
+    /// Whether to wrap inline content outside of any block in a paragraph, as
+    /// can occur in hand-built event streams
+    #[serde(default)]
+    pub wrap_loose_text: bool,
+}
+
+fn default_excerpt_marker() -> String {
+    "<!-- more -->".to_string()
+}
+
+fn default_document_separator() -> String {
+    "<hr class=\"doc-sep\">".to_string()
+}
+
+/// Rendering of emoji shortcodes like `:smile:`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmojiMode {
+    /// Leave shortcodes as written
+    #[default]
+    Off,
+    /// Replace known shortcodes with their Unicode emoji
+    Unicode,
+    /// Replace known shortcodes with an `<img>` whose source is the given
+    /// template, with `{name}` replaced by the shortcode name
+    Image(String),
+}
+
+/// Line ending style for generated HTML
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineEnding {
+    /// Unix-style `\n`
+    #[default]
+    Lf,
+    /// Windows-style `\r\n`
+    CrLf,
+}
+
+impl LineEnding {
+    /// The line ending as a string slice
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Policy for events the renderer does not know how to render
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownPolicy {
+    /// Silently drop the event
+    #[default]
+    Ignore,
+    /// Emit the construct inside an HTML comment: its source text when rendering
+    /// with `run_with_offsets`, otherwise its text content
+    Comment,
+    /// Abort rendering with an `HtmlError::Render`
+    Error,
+}
+
+/// Quote character used around HTML attribute values
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttrQuote {
+    /// Double quotes (`"`), escaping embedded double quotes as `&quot;`
+    #[default]
+    Double,
+    /// Single quotes (`'`), escaping embedded single quotes as `&#x27;`
+    Single,
+}
+
+impl AttrQuote {
+    /// The quote character as a string slice
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttrQuote::Double => "\"",
+            AttrQuote::Single => "'",
+        }
+    }
+}
+
+/// Where heading IDs come from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdSource {
+    /// Use an explicit `{#id}` when given, otherwise generate one
+    #[default]
+    ExplicitThenGenerated,
+    /// Always generate IDs, ignoring explicit ones
+    GeneratedOnly,
+    /// Only use explicit IDs; headings without one get no ID
+    ExplicitOnly,
+}
+
+/// What to do when a generated heading ID is already in use
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DupPolicy {
+    /// Keep the duplicate ID
+    #[default]
+    Keep,
+    /// Suffix the ID with `-1`, `-2`, ... until it is unique
+    Suffix,
+    /// Leave the heading without an ID
+    Skip,
+    /// Fail rendering with [`HtmlError::Render`]
+    Error,
 }
 
 /// Configuration options for different Markdown elements
@@ -35,6 +229,39 @@ pub struct ElementOptions {
     pub links: LinkOptions,
     /// Options for code blocks
     pub code_blocks: CodeBlockOptions,
+    /// Options for ordered and unordered lists
+    #[serde(default)]
+    pub lists: ListOptions,
+    /// Options for images
+    #[serde(default)]
+    pub images: ImageOptions,
+    /// Options for tables
+    #[serde(default)]
+    pub tables: TableOptions,
+    /// Options for inline code spans
+    #[serde(default)]
+    pub code_spans: CodeSpanOptions,
+    /// Options for paragraphs
+    #[serde(default)]
+    pub paragraphs: ParagraphOptions,
+    /// Options for definition lists
+    #[serde(default)]
+    pub definition_lists: DefinitionListOptions,
+    /// Options for strikethrough text
+    #[serde(default)]
+    pub strikethrough: StrikethroughOptions,
+    /// Options for task lists
+    #[serde(default)]
+    pub task_lists: TaskListOptions,
+    /// Options for horizontal rules
+    #[serde(default)]
+    pub hr: HrOptions,
+    /// Options for block quotes
+    #[serde(default)]
+    pub blockquotes: BlockquoteOptions,
+    /// Options for footnotes
+    #[serde(default)]
+    pub footnotes: FootnoteOptions,
 }
 
 /// Configuration options for headings
@@ -47,6 +274,85 @@ pub struct HeadingOptions {
     /// CSS classes to add to different heading levels
     #[serde(deserialize_with = "deserialize_heading_map")]
     pub level_classes: HashMap<u8, String>,
+    /// Deepest heading level that receives an ID
+    #[serde(default = "default_id_max_level")]
+    pub id_max_level: u8,
+    /// Whether to use explicit IDs, generated IDs, or both
+    #[serde(default)]
+    pub id_source: IdSource,
+    /// Whether to generate IDs from the heading text instead of the heading level
+    #[serde(default)]
+    pub id_from_content: bool,
+    /// What to do when a generated ID is already used in the document
+    #[serde(default)]
+    pub on_duplicate_id: DupPolicy,
+    /// ID used instead of the heading text when it has no characters usable in an ID
+    #[serde(default = "default_empty_id_fallback")]
+    pub empty_id_fallback: String,
+    /// Whether to prefix heading text with hierarchical section numbers, like `1.2.`
+    #[serde(default)]
+    pub auto_number: bool,
+    /// Whether to add a `data-level` attribute with the heading level
+    #[serde(default)]
+    pub emit_level_attr: bool,
+    /// Whether to add a `data-heading-syntax` attribute telling `setext`
+    /// (underlined) headings from `atx` (`#`) headings
+    ///
+    /// Only takes effect when rendering with
+    /// [`run_with_offsets`](crate::HtmlRenderer::run_with_offsets).
+    #[serde(default)]
+    pub mark_syntax: bool,
+}
+
+impl HeadingOptions {
+    /// The ID a heading receives, given its level, plain text and explicit `{#id}`, if any
+    ///
+    /// The ID is recorded in `ids`, and generated IDs that are already used
+    /// are handled according to `on_duplicate_id`.
+    pub(crate) fn heading_id(
+        &self,
+        level: u8,
+        text: &str,
+        explicit: Option<&str>,
+        ids: &mut HeadingIds,
+    ) -> Result<Option<String>, HtmlError> {
+        if !self.add_ids || level > self.id_max_level {
+            return Ok(None);
+        }
+        let explicit = match self.id_source {
+            IdSource::GeneratedOnly => None,
+            _ => explicit,
+        };
+        let generated = match explicit {
+            Some(id) => return Ok(Some(ids.assign(id.to_string(), false))),
+            None if self.id_source == IdSource::ExplicitOnly => return Ok(None),
+            None if self.id_from_content => {
+                let slug = match sanitize_id(text) {
+                    slug if slug.is_empty() => self.empty_id_fallback.clone(),
+                    slug => slug,
+                };
+                format!("{}{}", self.id_prefix, slug)
+            }
+            None => format!("{}{}", self.id_prefix, level),
+        };
+        match self.on_duplicate_id {
+            DupPolicy::Keep => Ok(Some(ids.assign(generated, false))),
+            DupPolicy::Skip if ids.contains(&generated) => Ok(None),
+            DupPolicy::Error if ids.contains(&generated) => Err(HtmlError::Render(format!(
+                "duplicate heading id '{}'",
+                generated
+            ))),
+            _ => Ok(Some(ids.assign(generated, true))),
+        }
+    }
+}
+
+fn default_id_max_level() -> u8 {
+    6
+}
+
+fn default_empty_id_fallback() -> String {
+    "section".to_string()
 }
 
 /// Configuration options for links
@@ -56,6 +362,50 @@ pub struct LinkOptions {
     pub nofollow_external: bool,
     /// Whether to add target="_blank" to external links
     pub open_external_blank: bool,
+    /// Whether to collapse whitespace (including newlines) in link and image titles
+    #[serde(default)]
+    pub normalize_titles: bool,
+    /// How to render links with an empty destination
+    #[serde(default)]
+    pub empty_href_policy: EmptyHref,
+    /// CSS class for same-page anchor links, whose destination starts with `#`
+    #[serde(default)]
+    pub anchor_class: Option<String>,
+    /// File extensions, like `pdf`, of link destinations that get a `download` attribute
+    #[serde(default)]
+    pub download_extensions: Vec<String>,
+    /// Whether to list the destinations of reference-style links in an
+    /// `<ol class="reference-index">` at the end of the document
+    #[serde(default)]
+    pub emit_reference_index: bool,
+}
+
+impl LinkOptions {
+    /// Whether the path of a link destination ends with one of `download_extensions`
+    pub(crate) fn is_download(&self, dest: &str) -> bool {
+        let path = dest.split(['?', '#']).next().unwrap_or_default();
+        let Some((_, extension)) = path.rsplit_once('.') else {
+            return false;
+        };
+        self.download_extensions.iter().any(|download| {
+            download
+                .trim_start_matches('.')
+                .eq_ignore_ascii_case(extension)
+        })
+    }
+}
+
+/// Rendering of links with an empty destination, such as `[text]()`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmptyHref {
+    /// Render the link with an empty `href`
+    #[default]
+    Keep,
+    /// Render only the link text, without an anchor
+    Drop,
+    /// Use the given URL as the `href`
+    Placeholder(String),
 }
 
 /// Configuration options for code blocks
@@ -64,7 +414,250 @@ pub struct CodeBlockOptions {
     /// Default language for code blocks that don't specify one
     pub default_language: Option<String>,
     /// Whether to add line numbers to code blocks
+    ///
+    /// Numbering starts at 1, or at the `startline` given in the fence info
+    /// string, e.g. ```` ```rust startline=42 ````.
     pub line_numbers: bool,
+    /// Mapping of language aliases (e.g. `js`) to canonical names (e.g. `javascript`)
+    #[serde(default)]
+    pub language_aliases: HashMap<String, String>,
+    /// Whether to strip leading and trailing blank lines from code blocks
+    #[serde(default)]
+    pub trim_blank_lines: bool,
+    /// Prefix of the language class on `<code>` elements; empty for the bare language name
+    #[serde(default = "default_class_prefix")]
+    pub class_prefix: String,
+    /// Whether to wrap code blocks in a `<div class="code-block">` holding a copy button
+    #[serde(default)]
+    pub copy_button: bool,
+    /// Languages whose code blocks render as a bare `<pre>` without an inner
+    /// `<code>`, e.g. for ASCII art
+    #[serde(default)]
+    pub bare_pre_languages: Vec<String>,
+    /// Whether to emit `key=value` pairs from the fence info string as
+    /// `data-*` attributes on the `<pre>` element
+    #[serde(default)]
+    pub info_attributes: bool,
+    /// Whether to render a `title` or `filename` from the fence info string as a
+    /// `<div class="code-filename">` header above the code block
+    #[serde(default)]
+    pub filename_header: bool,
+    /// Whether to keep the language as written in a `data-lang-original`
+    /// attribute when it is replaced through `language_aliases`
+    #[serde(default)]
+    pub keep_original_language: bool,
+    /// Whether to leave out code blocks holding only whitespace
+    #[serde(default)]
+    pub drop_empty: bool,
+    /// Families of languages, keyed by the language name after alias
+    /// resolution, emitted as a `data-lang-family` attribute for styling
+    #[serde(default)]
+    pub language_families: HashMap<String, String>,
+}
+
+fn default_class_prefix() -> String {
+    "language-".to_string()
+}
+
+/// Configuration options for lists
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ListOptions {
+    /// Whether to add `first`/`last` classes to the first and last item of each list
+    pub mark_first_last: bool,
+    /// Whether to add an explicit `value` attribute to items of ordered lists
+    pub explicit_values: bool,
+    /// Marker name emitted as a `data-marker` attribute on unordered lists
+    pub unordered_marker: Option<String>,
+    /// Whether to emit the `start` attribute on ordered lists starting at 1
+    pub always_emit_start: bool,
+    /// Whether to add a `data-depth` attribute with the nesting level to each
+    /// list, starting from 1
+    pub depth_attribute: bool,
+}
+
+/// Configuration options for horizontal rules
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HrOptions {
+    /// Element that thematic breaks render as
+    pub render_as: HrRender,
+    /// Whether to drop thematic breaks inside list items
+    pub suppress_in_lists: bool,
+    /// Whether to number thematic breaks with a `data-section` attribute,
+    /// starting from 1
+    pub number_sections: bool,
+}
+
+/// Rendering of thematic breaks (`---`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HrRender {
+    /// Render an `<hr>` element
+    #[default]
+    Hr,
+    /// Render an empty `<div>` with the given class, e.g. for a decorative divider
+    Div(String),
+}
+
+/// Configuration options for task lists
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TaskListOptions {
+    /// Whether to add a `contains-task-list` class to lists with task items
+    pub mark_containing_list: bool,
+    /// Whether to wrap the checkbox and text of task items in a `<label>`
+    pub wrap_label: bool,
+}
+
+/// Configuration options for strikethrough text
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct StrikethroughOptions {
+    /// Element used for strikethrough text
+    pub tag: StrikeTag,
+}
+
+/// Element used to render strikethrough text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StrikeTag {
+    /// `<del>`, marking deleted content
+    #[default]
+    Del,
+    /// `<s>`, marking content that is no longer relevant
+    S,
+}
+
+impl StrikeTag {
+    /// The tag name as a string slice
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StrikeTag::Del => "del",
+            StrikeTag::S => "s",
+        }
+    }
+}
+
+/// Configuration options for footnotes
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct FootnoteOptions {
+    /// Whether to render all footnote definitions at the end of the document,
+    /// wherever they appear in the source
+    pub collect_at_end: bool,
+}
+
+/// Configuration options for block quotes
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct BlockquoteOptions {
+    /// Element used for block quotes
+    pub tag: BlockquoteTag,
+    /// Whether to drop the `<p>` of a block quote holding a single paragraph
+    pub unwrap_single_paragraph: bool,
+}
+
+/// Element used to render block quotes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockquoteTag {
+    /// `<blockquote>`, for quoted content
+    #[default]
+    Blockquote,
+    /// `<aside>`, for tangential content such as pull quotes
+    Aside,
+}
+
+impl BlockquoteTag {
+    /// The tag name as a string slice
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlockquoteTag::Blockquote => "blockquote",
+            BlockquoteTag::Aside => "aside",
+        }
+    }
+}
+
+/// Configuration options for definition lists
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DefinitionListOptions {
+    /// CSS class to add to `<dl>` elements
+    pub class: Option<String>,
+    /// Whether to give each `<dt>` an ID derived from its term, for deep linking
+    pub term_ids: bool,
+}
+
+/// Configuration options for paragraphs
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ParagraphOptions {
+    /// Whether to keep soft breaks as literal newlines inside paragraphs marked
+    /// with a `preserve-newlines` class, for styling with `white-space: pre-wrap`
+    pub preserve_newlines: bool,
+    /// CSS class for the first top-level paragraph of the document
+    pub lead_class: Option<String>,
+    /// CSS class for paragraphs whose only content is an image
+    pub image_only_class: Option<String>,
+}
+
+/// Configuration options for inline code spans
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CodeSpanOptions {
+    /// Marker that renders a code span as `<kbd>` when its content is
+    /// prefixed with `<marker>:`, e.g. `` `kbd:Ctrl+C` ``
+    pub kbd_language: Option<String>,
+    /// Whether to keep the single space the parser strips from each end of a
+    /// code span like `` ` code ` ``; only applies when rendering with offsets
+    pub preserve_edge_space: bool,
+    /// Whether to add `<wbr>` line break opportunities after `.`, `/`, `::`
+    /// and `-`, so long names can wrap
+    pub insert_wbr: bool,
+}
+
+/// Configuration options for images
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ImageOptions {
+    /// Whether to wrap images in a link pointing at the image source
+    pub link_to_source: bool,
+    /// Value of the `sizes` attribute for images given a `srcset`
+    pub default_sizes: Option<String>,
+    /// Image shown when an image fails to load, through an `onerror` handler
+    ///
+    /// Inline event handlers run script, so this should be a trusted, static
+    /// URL. It is percent-encoded before being placed in the handler, and URLs
+    /// with a scheme other than `http` or `https` are ignored.
+    pub fallback_src: Option<String>,
+    /// Width given to images, unless set by their title or attributes
+    ///
+    /// Setting a default width or height also enables dimensions in image
+    /// titles, given as a trailing `=WxH`.
+    pub default_width: Option<u32>,
+    /// Height given to images, unless set by their title or attributes
+    pub default_height: Option<u32>,
+    /// Class added to the first image of the document, e.g. to use it as a cover
+    pub first_image_class: Option<String>,
+}
+
+/// Configuration options for tables
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TableOptions {
+    /// Whether to add `scope` attributes to header cells, rendering the first
+    /// cell of each body row as a row header
+    pub header_scopes: bool,
+    /// Maximum number of columns to render; cells beyond it are dropped and
+    /// the affected rows get a `data-truncated` attribute
+    pub max_columns: Option<usize>,
+    /// Classes for the cells of each column, by position; columns beyond the
+    /// list get no class
+    pub column_classes: Vec<String>,
+    /// Whether to render tables as definition lists for narrow layouts, with
+    /// each body cell as a definition of its column header
+    pub as_definition_list: bool,
 }
 
 /// Custom attribute mappings for HTML elements
@@ -73,6 +666,13 @@ pub struct AttributeMappings {
     /// Mapping of element names to their attributes
     #[serde(deserialize_with = "deserialize_nested_string_map")]
     pub element_attributes: HashMap<String, HashMap<String, String>>,
+    /// Drop repeated class names when classes from several sources are merged
+    #[serde(default = "default_dedupe_classes")]
+    pub dedupe_classes: bool,
+}
+
+fn default_dedupe_classes() -> bool {
+    true
 }
 
 impl Default for HtmlConfig {
@@ -83,28 +683,115 @@ impl Default for HtmlConfig {
                 break_on_newline: true,
                 xhtml_style: false,
                 pretty_print: true,
+                attribute_quote: AttrQuote::Double,
+                unknown_event_policy: UnknownPolicy::Ignore,
+                drop_empty_paragraphs: false,
+                autolink_bare_urls: false,
+                line_ending: LineEnding::Lf,
+                schema_org: false,
+                pandoc_sup_sub: false,
+                strict_nesting: false,
+                detect_dates: false,
+                global_block_attributes: HashMap::new(),
+                document_separator: default_document_separator(),
+                break_with_newline: false,
+                emoji: EmojiMode::Off,
+                trim_trailing_whitespace: false,
+                max_inline_nesting: None,
+                excerpt_marker: default_excerpt_marker(),
+                commonmark_strict: false,
+                encode_non_ascii: false,
+                encode_non_ascii_in_code: false,
+                amp_mode: false,
+                wrap_loose_text: false,
             },
             elements: ElementOptions {
                 headings: HeadingOptions {
                     add_ids: true,
                     id_prefix: "heading-".to_string(),
                     level_classes: HashMap::new(),
+                    id_max_level: 6,
+                    id_source: IdSource::ExplicitThenGenerated,
+                    id_from_content: false,
+                    on_duplicate_id: DupPolicy::Keep,
+                    empty_id_fallback: "section".to_string(),
+                    auto_number: false,
+                    emit_level_attr: false,
+                    mark_syntax: false,
                 },
                 links: LinkOptions {
                     nofollow_external: true,
                     open_external_blank: true,
+                    normalize_titles: false,
+                    empty_href_policy: EmptyHref::Keep,
+                    anchor_class: None,
+                    download_extensions: Vec::new(),
+                    emit_reference_index: false,
                 },
                 code_blocks: CodeBlockOptions {
                     default_language: None,
                     line_numbers: false,
+                    language_aliases: HashMap::new(),
+                    trim_blank_lines: false,
+                    class_prefix: "language-".to_string(),
+                    copy_button: false,
+                    bare_pre_languages: Vec::new(),
+                    info_attributes: false,
+                    filename_header: false,
+                    keep_original_language: false,
+                    drop_empty: false,
+                    language_families: HashMap::new(),
                 },
+                lists: ListOptions::default(),
+                images: ImageOptions::default(),
+                tables: TableOptions::default(),
+                code_spans: CodeSpanOptions::default(),
+                paragraphs: ParagraphOptions::default(),
+                definition_lists: DefinitionListOptions::default(),
+                strikethrough: StrikethroughOptions::default(),
+                task_lists: TaskListOptions::default(),
+                hr: HrOptions::default(),
+                blockquotes: BlockquoteOptions::default(),
+                footnotes: FootnoteOptions::default(),
             },
             attributes: AttributeMappings {
                 element_attributes: HashMap::new(),
+                dedupe_classes: true,
             },
             #[cfg(feature = "syntect")]
             syntect: None,
+            hooks: RenderHooks::default(),
+        }
+    }
+}
+
+impl HtmlConfig {
+    /// The configuration with the overrides of `html.commonmark_strict` applied
+    pub(crate) fn resolved(mut self) -> Self {
+        if self.html.commonmark_strict {
+            self.html.break_on_newline = false;
+            self.html.break_with_newline = true;
+            self.html.xhtml_style = true;
+            self.html.trim_trailing_whitespace = true;
+            self.elements.headings.add_ids = false;
+            self.elements.links.nofollow_external = false;
+            self.elements.links.open_external_blank = false;
+        }
+        self
+    }
+
+    /// Parser options used by the functions that render Markdown source
+    ///
+    /// Front matter blocks are always recognized, so they are never rendered
+    /// as content. Explicit heading attributes (`{#id .class}`) are recognized
+    /// unless `html.commonmark_strict` is set.
+    pub(crate) fn parser_options(&self) -> Options {
+        let mut options = Options::ENABLE_YAML_STYLE_METADATA_BLOCKS
+            | Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS;
+        if !self.html.commonmark_strict {
+            options |= Options::ENABLE_HEADING_ATTRIBUTES;
         }
+        options
     }
 }
 
@@ -0,0 +1,76 @@
+use pulldown_cmark_escape::StrWrite;
+use std::fmt;
+
+/// Writer that discards its output and only counts the bytes written
+///
+/// Useful for measuring the size of rendered HTML before allocating a buffer
+/// for it.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CountingWriter {
+    count: usize,
+}
+
+impl CountingWriter {
+    /// Create a new CountingWriter with a count of zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of bytes written so far
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl StrWrite for CountingWriter {
+    type Error = fmt::Error;
+
+    fn write_str(&mut self, s: &str) -> Result<(), Self::Error> {
+        self.count += s.len();
+        Ok(())
+    }
+
+    fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> Result<(), Self::Error> {
+        fmt::write(self, args)
+    }
+}
+
+impl fmt::Write for CountingWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.count += s.len();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html::{push_html, rendered_len, HtmlConfig};
+    use pulldown_cmark::Parser;
+
+    #[test]
+    fn test_counting_writer() {
+        let mut writer = CountingWriter::new();
+        StrWrite::write_str(&mut writer, "<p>").unwrap();
+        StrWrite::write_fmt(&mut writer, format_args!("{}", "héllo")).unwrap();
+        assert_eq!(writer.count(), 9);
+    }
+
+    #[test]
+    fn test_rendered_len_matches_output() {
+        let config = HtmlConfig::default();
+        let documents = [
+            "",
+            "# Hello\n\nThis is a test.",
+            "* One\n* Two\n\n1. First\n2. Second",
+            "Some *emphasis*, **strong** and `code` with a [link](https://example.com).",
+            "```rust\nfn main() {}\n```\n\n> Quoted ünïcödé text",
+        ];
+
+        for markdown in documents {
+            let mut output = String::new();
+            push_html(&mut output, Parser::new(markdown), &config).unwrap();
+            assert_eq!(rendered_len(markdown, &config).unwrap(), output.len());
+        }
+    }
+}
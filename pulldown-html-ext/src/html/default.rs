@@ -17,7 +17,7 @@ impl<W: StrWrite> HtmlWriterBase<W> {
     pub fn new(writer: W, config: HtmlConfig) -> Self {
         Self {
             writer,
-            config,
+            config: config.resolved(),
             state: HtmlState::new(),
         }
     }
@@ -141,4 +141,17 @@ mod tests {
         writer.get_state().currently_in_code_block = false;
         assert!(!writer.get_state().currently_in_code_block);
     }
+
+    #[test]
+    fn test_checkbox() {
+        let mut output = String::new();
+        let mut writer = DefaultHtmlWriter::new(FmtWriter(&mut output), HtmlConfig::default());
+
+        writer.checkbox(true).unwrap();
+        writer.checkbox(false).unwrap();
+        assert_eq!(
+            output,
+            "<input type=\"checkbox\" disabled checked><input type=\"checkbox\" disabled>"
+        );
+    }
 }
@@ -0,0 +1,59 @@
+use pulldown_cmark::{Event, MetadataBlockKind, Parser, Tag};
+
+use crate::html::{push_html, HtmlConfig, Result};
+
+/// A metadata block found at the start of a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrontMatter {
+    /// Syntax of the block: `---` delimited YAML or `+++` delimited TOML
+    pub kind: MetadataBlockKind,
+    /// Contents of the block, without the delimiters
+    pub raw: String,
+}
+
+/// Renders a Markdown document to HTML, returning its front matter separately
+///
+/// A metadata block at the start of the document is not rendered; its raw
+/// contents are returned for the caller to parse.
+///
+/// # Arguments
+///
+/// * `markdown` - The Markdown source to render
+/// * `config` - Configuration for HTML rendering
+///
+/// # Example
+///
+/// ```rust
+/// use pulldown_html_ext::{render_with_frontmatter, HtmlConfig};
+///
+/// let config = HtmlConfig::default();
+/// let (front_matter, html) =
+///     render_with_frontmatter("---\ntitle: Hello\n---\n# Hello", &config).unwrap();
+/// assert_eq!(front_matter.unwrap().raw, "title: Hello\n");
+/// assert!(html.starts_with("<h1"));
+/// ```
+pub fn render_with_frontmatter(
+    markdown: &str,
+    config: &HtmlConfig,
+) -> Result<(Option<FrontMatter>, String)> {
+    let mut parser = Parser::new_ext(markdown, config.parser_options()).peekable();
+
+    let mut front_matter = None;
+    if let Some(Event::Start(Tag::MetadataBlock(kind))) = parser.peek() {
+        let kind = *kind;
+        parser.next();
+        let mut raw = String::new();
+        for event in parser.by_ref() {
+            match event {
+                Event::Text(text) => raw.push_str(&text),
+                Event::End(_) => break,
+                _ => {}
+            }
+        }
+        front_matter = Some(FrontMatter { kind, raw });
+    }
+
+    let mut output = String::new();
+    push_html(&mut output, parser, config)?;
+    Ok((front_matter, output))
+}
@@ -0,0 +1,74 @@
+use pulldown_cmark::{Event, HeadingLevel, Parser};
+use pulldown_cmark_escape::FmtWriter;
+
+use crate::html::config::DupPolicy;
+use crate::html::{DefaultHtmlWriter, HtmlConfig, HtmlRenderer, HtmlWriter, Result};
+
+/// A heading found in a document, as collected by [`extract_headings`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadingInfo {
+    /// Level of the heading
+    pub level: HeadingLevel,
+    /// Plain text content of the heading, without inline formatting
+    pub text: String,
+    /// ID the heading receives when rendered, if any
+    pub id: Option<String>,
+}
+
+/// Collects the headings of a document, for building navigation
+///
+/// The document is run through the renderer and the output discarded, so the
+/// IDs are exactly those of the rendered HTML, including the suffixes that
+/// keep generated IDs unique when `on_duplicate_id` asks for them. Front
+/// matter and heading attributes are recognized, as in the other functions
+/// taking Markdown source. Duplicate headings that would fail rendering under
+/// [`DupPolicy::Error`](crate::DupPolicy::Error) are reported without an ID.
+///
+/// # Arguments
+///
+/// * `markdown` - The Markdown source to scan
+/// * `config` - Configuration for HTML rendering
+///
+/// # Example
+///
+/// ```rust
+/// use pulldown_html_ext::{extract_headings, HtmlConfig};
+///
+/// let headings = extract_headings("# Intro\n## Setup {#setup}", &HtmlConfig::default()).unwrap();
+/// assert_eq!(headings[0].text, "Intro");
+/// assert_eq!(headings[0].id.as_deref(), Some("heading-1"));
+/// assert_eq!(headings[1].id.as_deref(), Some("setup"));
+/// ```
+pub fn extract_headings(markdown: &str, config: &HtmlConfig) -> Result<Vec<HeadingInfo>> {
+    let options = config.parser_options();
+    let mut config = config.clone();
+    if config.elements.headings.on_duplicate_id == DupPolicy::Error {
+        config.elements.headings.on_duplicate_id = DupPolicy::Skip;
+    }
+
+    let mut output = String::new();
+    let mut writer = DefaultHtmlWriter::new(FmtWriter(&mut output), config);
+    writer.get_state().collected_headings = Some(Vec::new());
+    let mut renderer = HtmlRenderer::new(writer);
+    renderer.run(Parser::new_ext(markdown, options))?;
+    Ok(renderer
+        .writer
+        .get_state()
+        .collected_headings
+        .take()
+        .unwrap_or_default())
+}
+
+/// Concatenates the text of inline events, dropping any formatting
+pub(crate) fn plain_text<'a, I>(events: I) -> String
+where
+    I: IntoIterator<Item = Event<'a>>,
+{
+    let mut text = String::new();
+    for event in events {
+        if let Event::Text(content) | Event::Code(content) = event {
+            text.push_str(&content);
+        }
+    }
+    text
+}
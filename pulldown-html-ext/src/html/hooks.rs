@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Renders TeX source to HTML; the flag is `true` for display math
+pub type MathRenderer = Arc<dyn Fn(&str, bool) -> String + Send + Sync>;
+
+/// Rewrites the text of a code block, given its language, before it is emitted
+pub type CodeTextTransform = Arc<dyn Fn(&str, Option<&str>) -> String + Send + Sync>;
+
+/// Rewrites raw HTML blocks before they are passed through to the output
+pub type RawHtmlFilter = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// Rewrites the source of an image, e.g. to point at a CDN
+pub type ImageSrcResolver = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// Computes the `srcset` of an image from its source, if it has one
+pub type SrcsetResolver = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// Produces HTML to insert after an element
+pub type ElementHook = Arc<dyn Fn() -> String + Send + Sync>;
+
+/// Generates an `id` for an element from its tag name, if it should have one
+pub type IdGenerator = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// Renders a complete heading from its level, ID and plain text
+pub type HeadingRenderer = Arc<dyn Fn(u8, &str, &str) -> String + Send + Sync>;
+
+/// Computes extra attributes for an element from its name and context
+pub type AttributeProvider = Arc<dyn Fn(&str, &ElementInfo) -> Vec<(String, String)> + Send + Sync>;
+
+/// Context about an element passed to an [`AttributeProvider`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ElementInfo<'a> {
+    /// Tag name of the element
+    pub tag: &'a str,
+    /// Level of a heading element
+    pub heading_level: Option<u8>,
+    /// Destination of a link, or source of an image
+    pub href: Option<&'a str>,
+}
+
+impl<'a> ElementInfo<'a> {
+    /// Context for an element with no extra information
+    pub fn new(tag: &'a str) -> Self {
+        Self {
+            tag,
+            ..Default::default()
+        }
+    }
+}
+
+/// Caller-provided callbacks that customize rendering
+///
+/// Hooks are not part of the deserializable configuration and must be set
+/// programmatically on [`HtmlConfig::hooks`](crate::HtmlConfig::hooks).
+#[derive(Clone, Default)]
+pub struct RenderHooks {
+    /// Renders math spans to pre-rendered HTML, e.g. through KaTeX
+    pub math_renderer: Option<MathRenderer>,
+    /// Adds per-element attributes on top of the static `element_attributes`
+    pub attribute_provider: Option<AttributeProvider>,
+    /// Rewrites code block text before highlighting and output, e.g. to redact secrets
+    pub code_text_transform: Option<CodeTextTransform>,
+    /// Rewrites raw HTML blocks, e.g. to neutralize template markers
+    pub raw_html_passthrough_filter: Option<RawHtmlFilter>,
+    /// Rewrites image sources before any other image handling
+    pub image_src_resolver: Option<ImageSrcResolver>,
+    /// Provides responsive image sources for `<img>` elements
+    pub srcset_resolver: Option<SrcsetResolver>,
+    /// Inserts HTML after every element with the given tag name, e.g. `h2`
+    pub element_hooks: HashMap<String, ElementHook>,
+    /// Generates IDs for paragraphs and list items, e.g. for deep linking
+    pub id_generator: Option<IdGenerator>,
+    /// Replaces the default heading markup, e.g. to add edit links; the ID is
+    /// empty when the heading has none
+    pub heading_renderer: Option<HeadingRenderer>,
+}
+
+impl fmt::Debug for RenderHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RenderHooks")
+            .field("math_renderer", &self.math_renderer.is_some())
+            .field("attribute_provider", &self.attribute_provider.is_some())
+            .field("code_text_transform", &self.code_text_transform.is_some())
+            .field(
+                "raw_html_passthrough_filter",
+                &self.raw_html_passthrough_filter.is_some(),
+            )
+            .field("image_src_resolver", &self.image_src_resolver.is_some())
+            .field("srcset_resolver", &self.srcset_resolver.is_some())
+            .field(
+                "element_hooks",
+                &self.element_hooks.keys().collect::<Vec<_>>(),
+            )
+            .field("id_generator", &self.id_generator.is_some())
+            .field("heading_renderer", &self.heading_renderer.is_some())
+            .finish()
+    }
+}
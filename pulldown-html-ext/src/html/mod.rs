@@ -5,8 +5,12 @@
 //! elements, attribute handling, and state management during rendering.
 
 mod config;
+mod counting;
 mod default;
 mod error;
+mod frontmatter;
+mod headings;
+mod hooks;
 mod state;
 mod writer;
 
@@ -14,19 +18,34 @@ mod writer;
 mod syntect;
 #[cfg(feature = "syntect")]
 pub use self::syntect::{
-    push_html_with_highlighting, SyntectConfig, SyntectConfigStyle, SyntectWriter,
+    push_html_with_highlighting, render_with_highlighting, CssMode, HighlightedHtml, SyntectConfig,
+    SyntectConfigStyle, SyntectWriter,
 };
-use pulldown_cmark::{Event, Tag, TagEnd};
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd, TextMergeStream};
 use pulldown_cmark_escape::{FmtWriter, IoWriter, StrWrite};
 use std::iter::Peekable;
+use std::ops::Range;
 
 pub use self::config::{
-    AttributeMappings, CodeBlockOptions, ElementOptions, HeadingOptions, HtmlConfig, HtmlOptions,
-    LinkOptions,
+    AttrQuote, AttributeMappings, BlockquoteOptions, BlockquoteTag, CodeBlockOptions,
+    CodeSpanOptions, DefinitionListOptions, DupPolicy, ElementOptions, EmojiMode, EmptyHref,
+    FootnoteOptions, HeadingOptions, HrOptions, HrRender, HtmlConfig, HtmlOptions, IdSource,
+    ImageOptions, LineEnding, LinkOptions, ListOptions, ParagraphOptions, StrikeTag,
+    StrikethroughOptions, TableOptions, TaskListOptions, UnknownPolicy,
 };
+pub use self::counting::CountingWriter;
 pub use self::default::DefaultHtmlWriter;
 pub use self::error::HtmlError;
-pub use self::state::{HtmlState, ListContext, TableContext};
+pub use self::frontmatter::{render_with_frontmatter, FrontMatter};
+use self::headings::plain_text;
+pub use self::headings::{extract_headings, HeadingInfo};
+pub use self::hooks::{
+    AttributeProvider, CodeTextTransform, ElementHook, ElementInfo, HeadingRenderer, IdGenerator,
+    ImageSrcResolver, MathRenderer, RawHtmlFilter, RenderHooks, SrcsetResolver,
+};
+pub use self::state::{
+    BlockContext, HeadingIds, HtmlState, ListContext, RenderStats, TableContext,
+};
 pub use self::writer::HtmlWriter;
 
 pub type Result<T> = std::result::Result<T, HtmlError>;
@@ -51,20 +70,212 @@ impl<W: StrWrite, H: HtmlWriter<W>> HtmlRenderer<W, H> {
     where
         I: Iterator<Item = Event<'a>>,
     {
-        let mut iter = iter.peekable();
+        // The parser splits text at characters like `[`, `&amp;` and `~`, so
+        // adjacent text is merged before looking for URLs and other patterns
+        let mut iter = TextMergeStream::new(iter).peekable();
+        self.writer.start_document()?;
+        self.process_events(&mut iter)?;
+        self.flush_footnotes()?;
+        self.writer.end_document()?;
+        self.writer.get_state().documents_rendered += 1;
+        Ok(())
+    }
+
+    /// Runs the renderer, first writing the configured `document_separator` if
+    /// the writer already holds an earlier document
+    pub fn run_with_separator<'a, I>(&mut self, iter: I) -> Result<()>
+    where
+        I: Iterator<Item = Event<'a>>,
+    {
+        if self.writer.get_state().documents_rendered > 0 {
+            let separator = self.writer.get_config().html.document_separator.clone();
+            self.writer.write_str(&separator)?;
+        }
+        self.run(iter)
+    }
+
+    /// Renders the footnote definitions held back by `footnotes.collect_at_end`
+    fn flush_footnotes(&mut self) -> Result<()> {
+        let events = std::mem::take(&mut self.writer.get_state().deferred_footnotes);
+        if events.is_empty() {
+            return Ok(());
+        }
+        self.writer.get_state().flushing_footnotes = true;
+        let result = self.process_events(&mut events.into_iter().peekable());
+        self.writer.get_state().flushing_footnotes = false;
+        result
+    }
+
+    /// Runs the renderer over an offset iterator, such as `Parser::into_offset_iter`
+    ///
+    /// Behaves like [`run`](Self::run), except that options depending on the
+    /// original source, such as `code_spans.preserve_edge_space` and
+    /// `headings.mark_syntax`, take effect, and constructs with no HTML
+    /// mapping reach `html.unknown_event_policy` as their source text.
+    pub fn run_with_offsets<'a, I>(&mut self, source: &'a str, iter: I) -> Result<()>
+    where
+        I: Iterator<Item = (Event<'a>, Range<usize>)>,
+    {
+        let preserve_edge_space = self
+            .writer
+            .get_config()
+            .elements
+            .code_spans
+            .preserve_edge_space;
+        let mark_syntax = self.writer.get_config().elements.headings.mark_syntax;
+        let mut in_unmapped = false;
+        self.run(iter.flat_map(move |(event, range)| {
+            let mut source_text = None;
+            let event = match event {
+                Event::Start(Tag::MetadataBlock(_)) => {
+                    // Replace the block's contents with its source slice, which
+                    // is what the unknown event policy receives
+                    in_unmapped = true;
+                    source_text = Some(Event::Text(source[range].into()));
+                    Some(event)
+                }
+                Event::End(TagEnd::MetadataBlock(_)) => {
+                    in_unmapped = false;
+                    Some(event)
+                }
+                _ if in_unmapped => None,
+                event => Some(Self::map_offset_event(
+                    source,
+                    event,
+                    range,
+                    preserve_edge_space,
+                    mark_syntax,
+                )),
+            };
+            event.into_iter().chain(source_text)
+        }))
+    }
+
+    /// Apply the source-dependent options of [`run_with_offsets`](Self::run_with_offsets) to an event
+    fn map_offset_event<'a>(
+        source: &'a str,
+        event: Event<'a>,
+        range: Range<usize>,
+        preserve_edge_space: bool,
+        mark_syntax: bool,
+    ) -> Event<'a> {
+        match event {
+            Event::Start(Tag::Heading {
+                level,
+                id,
+                classes,
+                mut attrs,
+            }) if mark_syntax => {
+                // A setext heading ends with a line of `=` or `-` underlining its text
+                let heading = &source[range];
+                let underline = heading
+                    .lines()
+                    .rev()
+                    .map(str::trim)
+                    .find(|line| !line.is_empty())
+                    .unwrap_or_default();
+                let setext = heading.trim_end().contains('\n')
+                    && (underline.chars().all(|c| c == '=') || underline.chars().all(|c| c == '-'));
+                let syntax = if setext { "setext" } else { "atx" };
+                attrs.push(("data-heading-syntax".into(), Some(syntax.into())));
+                Event::Start(Tag::Heading {
+                    level,
+                    id,
+                    classes,
+                    attrs,
+                })
+            }
+            Event::Code(text) if preserve_edge_space => {
+                // The parser strips one space from both ends of the span's content
+                let content = source[range].trim_matches('`');
+                if content.len() >= text.len() + 2
+                    && content.starts_with([' ', '\n'])
+                    && content.ends_with([' ', '\n'])
+                {
+                    Event::Code(format!(" {} ", text).into())
+                } else {
+                    Event::Code(text)
+                }
+            }
+            event => event,
+        }
+    }
+
+    fn process_events<'a, I>(&mut self, iter: &mut Peekable<I>) -> Result<()>
+    where
+        I: Iterator<Item = Event<'a>>,
+    {
+        // Whether the last event left inline content on the current line
+        let mut after_inline = false;
         while let Some(event) = iter.next() {
+            let block = is_block_event(&event);
+            if block {
+                // A task label only wraps the first run of inline content of its item
+                self.writer.end_task_label()?;
+            }
+            if block && after_inline && matches!(event, Event::Start(_)) {
+                // Such as a list nested in a tight list item
+                self.strict_newline()?;
+            }
+            after_inline = !block;
+
+            let loose = !block
+                && !matches!(event, Event::End(_))
+                && self.writer.get_config().html.wrap_loose_text
+                && self.writer.get_state().open_tags.is_empty();
+            if loose {
+                // Wrap the run of inline events in a paragraph
+                let mut events = vec![Event::Start(Tag::Paragraph), event];
+                while let Some(next) = iter.next_if(|next| !is_block_event(next)) {
+                    events.push(next);
+                }
+                events.push(Event::End(TagEnd::Paragraph));
+                self.process_events(&mut events.into_iter().peekable())?;
+                continue;
+            }
+
             match event {
-                Event::Start(tag) => self.handle_start(&mut iter, tag)?,
-                Event::End(tag) => self.handle_end(tag)?,
-                Event::Text(text) => self.writer.text(&text)?,
+                Event::Start(tag) => {
+                    self.open_tag(&tag);
+                    self.handle_start(iter, tag)?
+                }
+                Event::End(tag) => {
+                    self.close_tag(tag)?;
+                    self.handle_end(tag)?
+                }
+                Event::Text(text) => {
+                    if !self.writer.get_state().currently_in_code_block {
+                        self.writer.get_state().stats.words += text
+                            .split_whitespace()
+                            .filter(|word| word.chars().any(char::is_alphanumeric))
+                            .count();
+                    }
+                    let trim = self.writer.get_config().html.trim_trailing_whitespace
+                        && !self.writer.get_state().currently_in_code_block;
+                    if trim {
+                        let at_line_end = matches!(iter.peek(), None | Some(Event::SoftBreak))
+                            || matches!(iter.peek(), Some(Event::End(tag)) if is_block_end(tag));
+                        self.writer.text(&trim_line_ends(&text, at_line_end))?
+                    } else {
+                        self.writer.text(&text)?
+                    }
+                }
                 Event::Code(text) => self.handle_inline_code(&text)?,
-                Event::Html(html) => self.writer.write_str(&html)?,
+                Event::Html(html) => self.writer.html_block(&html)?,
                 Event::SoftBreak => self.writer.soft_break()?,
                 Event::HardBreak => self.writer.hard_break()?,
-                Event::Rule => self.writer.horizontal_rule()?,
+                Event::Rule => {
+                    let in_list = !self.writer.get_state().list_stack.is_empty();
+                    if !(in_list && self.writer.get_config().elements.hr.suppress_in_lists) {
+                        self.writer.horizontal_rule()?;
+                        self.pretty_newline()?;
+                    }
+                }
                 Event::FootnoteReference(name) => self.writer.footnote_reference(&name)?,
                 Event::TaskListMarker(checked) => self.writer.task_list_item(checked)?,
-                Event::InlineMath(_) | Event::DisplayMath(_) | Event::InlineHtml(_) => todo!(),
+                Event::InlineHtml(html) => self.writer.html_raw(&html)?,
+                Event::InlineMath(text) => self.writer.inline_math(&text)?,
+                Event::DisplayMath(text) => self.writer.display_math(&text)?,
             }
         }
         Ok(())
@@ -78,22 +289,207 @@ impl<W: StrWrite, H: HtmlWriter<W>> HtmlRenderer<W, H> {
     where
         I: Iterator<Item = Event<'a>>,
     {
+        if let Tag::FootnoteDefinition(_) = tag {
+            let collect_at_end = self.writer.get_config().elements.footnotes.collect_at_end;
+            if collect_at_end && !self.writer.get_state().flushing_footnotes {
+                let events = collect_until_end(iter);
+                let deferred = &mut self.writer.get_state().deferred_footnotes;
+                deferred.push(Event::Start(tag.into_static()));
+                deferred.extend(events.into_iter().map(Event::into_static));
+                self.skip_tag();
+                return Ok(());
+            }
+        }
+
+        if let Tag::TableCell = tag {
+            if self.writer.is_cell_truncated() {
+                collect_until_end(iter);
+                self.skip_tag();
+                return Ok(());
+            }
+        }
+
+        if let Tag::CodeBlock(kind) = &tag {
+            if self.writer.get_config().elements.code_blocks.drop_empty {
+                // Buffer the block so it can be dropped before anything is written
+                let events = collect_until_end(iter);
+                if is_empty_block(&events) {
+                    self.skip_tag();
+                    return Ok(());
+                }
+                self.writer.get_state().stats.code_blocks += 1;
+                self.writer.start_code_block(kind.clone())?;
+                return self.process_events(&mut events.into_iter().peekable());
+            }
+        }
+
+        if let Tag::Emphasis | Tag::Strong = tag {
+            let max = self.writer.get_config().html.max_inline_nesting;
+            let state = self.writer.get_state();
+            if max.is_some_and(|max| state.emphasis_depth >= max) {
+                // Flatten everything inside to text so the output stays bounded
+                let events: Vec<_> = std::iter::once(Event::Start(tag))
+                    .chain(collect_until_end(iter))
+                    .map(|event| match event {
+                        Event::Start(Tag::Emphasis) | Event::End(TagEnd::Emphasis) => {
+                            Event::Text("*".into())
+                        }
+                        Event::Start(Tag::Strong) | Event::End(TagEnd::Strong) => {
+                            Event::Text("**".into())
+                        }
+                        event => event,
+                    })
+                    .collect();
+                self.process_events(&mut events.into_iter().peekable())?;
+                self.skip_tag();
+                return Ok(());
+            }
+            state.emphasis_depth += 1;
+        }
+
+        let stats = &mut self.writer.get_state().stats;
         match tag {
-            Tag::Paragraph => self.writer.start_paragraph()?,
+            Tag::Heading { .. } => stats.headings += 1,
+            Tag::Link { .. } => stats.links += 1,
+            Tag::Image { .. } => stats.images += 1,
+            Tag::CodeBlock(_) => stats.code_blocks += 1,
+            _ => {}
+        }
+
+        let context = match tag {
+            Tag::BlockQuote(_) => Some(BlockContext::BlockQuote),
+            Tag::Item => Some(BlockContext::ListItem),
+            Tag::TableCell => Some(BlockContext::TableCell),
+            Tag::FootnoteDefinition(_) => Some(BlockContext::FootnoteDefinition),
+            Tag::DefinitionListTitle => Some(BlockContext::DefinitionListTitle),
+            Tag::DefinitionListDefinition => Some(BlockContext::DefinitionListDefinition),
+            _ => None,
+        };
+        if let Some(context) = context {
+            self.writer.get_state().block_contexts.push(context);
+        }
+
+        match tag {
+            Tag::Paragraph => {
+                let config = self.writer.get_config();
+                let drop_empty = config.html.drop_empty_paragraphs;
+                let mark_image_only = config.elements.paragraphs.image_only_class.is_some();
+                if drop_empty || mark_image_only {
+                    // Buffer the paragraph so its content can be inspected before opening it
+                    let events = collect_until_end(iter);
+                    if drop_empty && is_empty_block(&events) {
+                        self.skip_tag();
+                    } else {
+                        self.writer.get_state().paragraph_image_only =
+                            mark_image_only && is_image_only(&events);
+                        self.writer.start_paragraph()?;
+                        self.process_events(&mut events.into_iter().peekable())?;
+                    }
+                } else {
+                    self.writer.start_paragraph()?
+                }
+            }
             Tag::Heading {
                 level,
                 id,
                 classes,
                 attrs,
-            } => self
-                .writer
-                .start_heading(level, id.as_deref(), &classes, &attrs)?,
-            Tag::BlockQuote(_) => self.writer.start_blockquote()?,
+            } => {
+                if let Some(renderer) = self.writer.get_config().hooks.heading_renderer.clone() {
+                    // Buffer the heading and hand its plain text to the custom renderer
+                    let events = collect_until_end(iter);
+                    let text = plain_text(events);
+                    let heading_id = self.writer.assign_heading_id(level, &text, id.as_deref())?;
+                    let html = renderer(level as u8, heading_id.as_deref().unwrap_or(""), &text);
+                    self.writer.write_str(&html)?;
+                    self.skip_tag();
+                    if let Some(name) = self.element_name(&TagEnd::Heading(level)) {
+                        self.run_element_hook(name)?;
+                    }
+                    self.pretty_newline()?;
+                } else if self.writer.get_config().elements.headings.id_from_content
+                    || self.writer.get_state().collected_headings.is_some()
+                {
+                    // Buffer the heading so its ID can be derived from the text
+                    let events = collect_until_end(iter);
+                    self.writer.get_state().heading_text = plain_text(events.iter().cloned());
+                    self.writer
+                        .start_heading(level, id.as_deref(), &classes, &attrs)?;
+                    self.process_events(&mut events.into_iter().peekable())?;
+                } else {
+                    self.writer
+                        .start_heading(level, id.as_deref(), &classes, &attrs)?
+                }
+            }
+            Tag::BlockQuote(_) => {
+                if self
+                    .writer
+                    .get_config()
+                    .elements
+                    .blockquotes
+                    .unwrap_single_paragraph
+                {
+                    // Buffer the quote to find out whether it is a single paragraph
+                    let mut events = collect_until_end(iter);
+                    if is_single_paragraph(&events) {
+                        // Keep the inline content and the end of the quote
+                        events.remove(events.len() - 2);
+                        events.remove(0);
+                    }
+                    self.writer.start_blockquote()?;
+                    self.strict_newline()?;
+                    self.process_events(&mut events.into_iter().peekable())?;
+                } else {
+                    self.writer.start_blockquote()?;
+                    self.strict_newline()?
+                }
+            }
             Tag::CodeBlock(kind) => self.writer.start_code_block(kind)?,
-            Tag::List(start) => self.writer.start_list(start)?,
-            Tag::Item => self.writer.start_list_item()?,
+            Tag::List(start) => {
+                let elements = &self.writer.get_config().elements;
+                let mark_first_last = elements.lists.mark_first_last;
+                let mark_tasks = elements.task_lists.mark_containing_list;
+                if mark_first_last || mark_tasks {
+                    // Buffer the whole list so its items can be inspected before opening it
+                    let events = collect_until_end(iter);
+                    if mark_first_last {
+                        let item_count = count_list_items(&events);
+                        self.writer
+                            .get_state()
+                            .list_item_positions
+                            .push((0, item_count));
+                    }
+                    if mark_tasks {
+                        self.writer.get_state().list_contains_tasks = contains_task_items(&events);
+                    }
+                    self.writer.start_list(start)?;
+                    self.strict_newline()?;
+                    self.process_events(&mut events.into_iter().peekable())?;
+                    if mark_first_last {
+                        self.writer.get_state().list_item_positions.pop();
+                    }
+                } else {
+                    self.writer.start_list(start)?;
+                    self.strict_newline()?
+                }
+            }
+            Tag::Item => {
+                self.writer.start_list_item()?;
+                // Block content of loose items starts on its own line
+                if matches!(iter.peek(), Some(Event::Start(tag)) if is_block_end(&tag.to_end())) {
+                    self.strict_newline()?
+                }
+            }
             Tag::FootnoteDefinition(name) => self.writer.start_footnote_definition(&name)?,
-            Tag::Table(alignments) => self.writer.start_table(alignments)?,
+            Tag::Table(alignments) => {
+                if self.writer.get_config().elements.tables.as_definition_list {
+                    let events = table_as_definition_list(collect_until_end(iter));
+                    self.skip_tag();
+                    self.process_events(&mut events.into_iter().peekable())?;
+                } else {
+                    self.writer.start_table(alignments)?
+                }
+            }
             Tag::TableHead => self.writer.start_table_head()?,
             Tag::TableRow => self.writer.start_table_row()?,
             Tag::TableCell => self.writer.start_table_cell()?,
@@ -105,27 +501,76 @@ impl<W: StrWrite, H: HtmlWriter<W>> HtmlRenderer<W, H> {
                 dest_url,
                 title,
                 id: _,
-            } => self.writer.start_link(link_type, &dest_url, &title)?,
+            } => {
+                let policy = &self.writer.get_config().elements.links.empty_href_policy;
+                match policy {
+                    EmptyHref::Drop if dest_url.is_empty() => {
+                        // Render the link text without the anchor
+                        let mut events = collect_until_end(iter);
+                        events.pop();
+                        self.process_events(&mut events.into_iter().peekable())?;
+                        self.skip_tag();
+                    }
+                    EmptyHref::Placeholder(href) if dest_url.is_empty() => {
+                        let href = href.clone();
+                        self.writer.start_link(link_type, &href, &title)?
+                    }
+                    _ => self.writer.start_link(link_type, &dest_url, &title)?,
+                }
+            }
             Tag::Image {
                 link_type,
                 dest_url,
                 title,
                 id: _,
-            } => self
-                .writer
-                .start_image(link_type, &dest_url, &title, iter)?,
+            } => {
+                // The image consumes its alt text events up to its end tag
+                self.writer
+                    .start_image(link_type, &dest_url, &title, iter)?;
+                self.skip_tag();
+                self.run_element_hook("img")?;
+            }
 
             Tag::DefinitionList => self.writer.start_definition_list()?,
-            Tag::DefinitionListTitle => self.writer.start_definition_list_title()?,
+            Tag::DefinitionListTitle => {
+                if self.writer.get_config().elements.definition_lists.term_ids {
+                    // Buffer the term so its ID can be derived from the text
+                    let events = collect_until_end(iter);
+                    self.writer.get_state().term_text = plain_text(events.iter().cloned());
+                    self.writer.start_definition_list_title()?;
+                    self.process_events(&mut events.into_iter().peekable())?;
+                } else {
+                    self.writer.start_definition_list_title()?
+                }
+            }
             Tag::DefinitionListDefinition => self.writer.start_definition_list_definition()?,
 
-            Tag::MetadataBlock(kind) => self.writer.start_metadata_block(&kind)?,
+            Tag::MetadataBlock(kind) => {
+                // The block has no HTML mapping, so its contents are handled by
+                // the unknown event policy; `run_with_offsets` passes them on as
+                // the block's source text
+                let events = collect_until_end(iter);
+                self.writer.start_metadata_block(&kind)?;
+                self.writer.unknown_event(&plain_text(events))?;
+                self.writer.end_metadata_block()?;
+                self.skip_tag();
+            }
             Tag::HtmlBlock => (),
         }
         Ok(())
     }
 
     fn handle_end(&mut self, tag: TagEnd) -> Result<()> {
+        let block = is_block_end(&tag);
+        let closes_context = matches!(
+            tag,
+            TagEnd::BlockQuote(_)
+                | TagEnd::Item
+                | TagEnd::TableCell
+                | TagEnd::FootnoteDefinition
+                | TagEnd::DefinitionListTitle
+                | TagEnd::DefinitionListDefinition
+        );
         match tag {
             TagEnd::Paragraph => self.writer.end_paragraph()?,
             TagEnd::Heading(level) => self.writer.end_heading(level)?,
@@ -139,22 +584,150 @@ impl<W: StrWrite, H: HtmlWriter<W>> HtmlRenderer<W, H> {
             TagEnd::TableHead => self.writer.end_table_head()?,
             TagEnd::TableRow => self.writer.end_table_row()?,
             TagEnd::TableCell => self.writer.end_table_cell()?,
-            TagEnd::Emphasis => self.writer.end_emphasis()?,
-            TagEnd::Strong => self.writer.end_strong()?,
+            TagEnd::Emphasis => {
+                let state = self.writer.get_state();
+                state.emphasis_depth = state.emphasis_depth.saturating_sub(1);
+                self.writer.end_emphasis()?
+            }
+            TagEnd::Strong => {
+                let state = self.writer.get_state();
+                state.emphasis_depth = state.emphasis_depth.saturating_sub(1);
+                self.writer.end_strong()?
+            }
             TagEnd::Strikethrough => self.writer.end_strikethrough()?,
             TagEnd::Link {} => self.writer.end_link()?,
             TagEnd::Image {} => self.writer.end_image()?,
             TagEnd::DefinitionList => self.writer.end_definition_list()?,
             TagEnd::DefinitionListTitle => self.writer.end_definition_list_title()?,
-            TagEnd::DefinitionListDefinition => self.writer.end_definition_list_title()?,
+            TagEnd::DefinitionListDefinition => self.writer.end_definition_list_definition()?,
 
             TagEnd::MetadataBlock(_) => self.writer.end_metadata_block()?,
             TagEnd::HtmlBlock => (),
         }
+        if closes_context {
+            self.writer.get_state().block_contexts.pop();
+        }
+        if let Some(name) = self.element_name(&tag) {
+            self.run_element_hook(name)?;
+        }
+        if block {
+            self.pretty_newline()?;
+        }
+        Ok(())
+    }
+
+    /// Name of the HTML element closed by an end tag, as used to key element hooks
+    fn element_name(&self, tag: &TagEnd) -> Option<&'static str> {
+        Some(match tag {
+            TagEnd::Paragraph => "p",
+            TagEnd::Heading(level) => match level {
+                HeadingLevel::H1 => "h1",
+                HeadingLevel::H2 => "h2",
+                HeadingLevel::H3 => "h3",
+                HeadingLevel::H4 => "h4",
+                HeadingLevel::H5 => "h5",
+                HeadingLevel::H6 => "h6",
+            },
+            TagEnd::BlockQuote(_) => self.writer.get_config().elements.blockquotes.tag.as_str(),
+            TagEnd::CodeBlock => "pre",
+            TagEnd::List(true) => "ol",
+            TagEnd::List(false) => "ul",
+            TagEnd::Item => "li",
+            TagEnd::Table => "table",
+            TagEnd::TableHead => "thead",
+            TagEnd::TableRow => "tr",
+            TagEnd::Emphasis => "em",
+            TagEnd::Strong => "strong",
+            TagEnd::Strikethrough => self.writer.get_config().elements.strikethrough.tag.as_str(),
+            TagEnd::Link => "a",
+            TagEnd::DefinitionList => "dl",
+            TagEnd::DefinitionListTitle => "dt",
+            TagEnd::DefinitionListDefinition => "dd",
+            _ => return None,
+        })
+    }
+
+    /// Writes the output of the element hook registered for an element, if any
+    fn run_element_hook(&mut self, name: &str) -> Result<()> {
+        if let Some(hook) = self.writer.get_config().hooks.element_hooks.get(name) {
+            let html = hook();
+            self.writer.write_str(&html)?;
+        }
+        Ok(())
+    }
+
+    /// Records a started tag when checking nesting
+    fn open_tag(&mut self, tag: &Tag) {
+        if self.tracks_tags() {
+            self.writer.get_state().open_tags.push(tag.to_end());
+        }
+    }
+
+    /// Checks an end tag against the most recently started tag when checking nesting
+    fn close_tag(&mut self, tag: TagEnd) -> Result<()> {
+        if !self.tracks_tags() {
+            return Ok(());
+        }
+        let expected = self.writer.get_state().open_tags.pop();
+        if !self.writer.get_config().html.strict_nesting {
+            return Ok(());
+        }
+        match expected {
+            Some(expected) if expected == tag => Ok(()),
+            Some(expected) => Err(HtmlError::Render(format!(
+                "mismatched end tag: expected {:?}, found {:?}",
+                expected, tag
+            ))),
+            None => Err(HtmlError::Render(format!(
+                "end tag {:?} without a matching start",
+                tag
+            ))),
+        }
+    }
+
+    /// Forgets the most recently started tag after its events were consumed
+    /// without reaching `handle_end`
+    fn skip_tag(&mut self) {
+        if self.tracks_tags() {
+            self.writer.get_state().open_tags.pop();
+        }
+    }
+
+    /// Whether the open tags are tracked, for checking nesting or finding loose text
+    fn tracks_tags(&self) -> bool {
+        let html = &self.writer.get_config().html;
+        html.strict_nesting || html.wrap_loose_text
+    }
+
+    /// Ends a line after a block element when pretty printing is enabled
+    fn pretty_newline(&mut self) -> Result<()> {
+        if self.writer.get_config().html.pretty_print {
+            self.writer.newline()?;
+        }
+        Ok(())
+    }
+
+    /// Write a newline where the reference renderer starts a new line after an opening tag
+    fn strict_newline(&mut self) -> Result<()> {
+        if self.writer.get_config().html.commonmark_strict {
+            self.pretty_newline()?;
+        }
         Ok(())
     }
 
     fn handle_inline_code(&mut self, text: &str) -> Result<()> {
+        let keys = self
+            .writer
+            .get_config()
+            .elements
+            .code_spans
+            .kbd_language
+            .as_deref()
+            .and_then(|marker| text.strip_prefix(marker)?.strip_prefix(':'));
+        if let Some(keys) = keys {
+            return self.writer.kbd(keys);
+        }
+
         self.writer.start_inline_code()?;
         self.writer.text(text)?;
         self.writer.end_inline_code()?;
@@ -162,6 +735,218 @@ impl<W: StrWrite, H: HtmlWriter<W>> HtmlRenderer<W, H> {
     }
 }
 
+/// Whether the end tag closes a block element that is followed by a newline when pretty printing
+fn is_block_end(tag: &TagEnd) -> bool {
+    matches!(
+        tag,
+        TagEnd::Paragraph
+            | TagEnd::Heading(_)
+            | TagEnd::BlockQuote(_)
+            | TagEnd::CodeBlock
+            | TagEnd::List(_)
+            | TagEnd::Item
+            | TagEnd::FootnoteDefinition
+            | TagEnd::Table
+            | TagEnd::TableHead
+            | TagEnd::TableRow
+            | TagEnd::DefinitionList
+            | TagEnd::DefinitionListTitle
+            | TagEnd::DefinitionListDefinition
+    )
+}
+
+/// Whether the event starts or ends a block, or is a block by itself
+fn is_block_event(event: &Event) -> bool {
+    match event {
+        // HTML and metadata blocks are blocks too, but their content ends its own line
+        Event::Start(Tag::HtmlBlock | Tag::MetadataBlock(_))
+        | Event::End(TagEnd::HtmlBlock | TagEnd::MetadataBlock(_)) => true,
+        Event::Start(tag) => is_block_end(&tag.to_end()),
+        Event::End(tag) => is_block_end(tag),
+        Event::Html(_) | Event::Rule => true,
+        _ => false,
+    }
+}
+
+/// Trims trailing spaces and tabs from each line of the text
+///
+/// The last line is only trimmed when `at_line_end` is set, so whitespace
+/// followed by more inline content or a hard break is kept.
+fn trim_line_ends(text: &str, at_line_end: bool) -> String {
+    let mut lines: Vec<&str> = text.split('\n').collect();
+    let last = lines.len() - 1;
+    for (i, line) in lines.iter_mut().enumerate() {
+        if i < last || at_line_end {
+            *line = line.trim_end_matches([' ', '\t']);
+        }
+    }
+    lines.join("\n")
+}
+
+/// Whether the events of a container, up to and including its end tag, are one paragraph
+fn is_single_paragraph(events: &[Event]) -> bool {
+    let paragraphs = events
+        .iter()
+        .filter(|event| matches!(event, Event::Start(Tag::Paragraph)))
+        .count();
+    paragraphs == 1
+        && matches!(events.first(), Some(Event::Start(Tag::Paragraph)))
+        && matches!(
+            events.iter().rev().nth(1),
+            Some(Event::End(TagEnd::Paragraph))
+        )
+}
+
+/// Turns the events of a table, after its start tag, into a definition list
+///
+/// Each body cell becomes a definition, preceded by its column header as the term.
+fn table_as_definition_list(events: Vec<Event>) -> Vec<Event> {
+    let mut header = Vec::new();
+    let mut rows = Vec::new();
+    let mut cell = Vec::new();
+    let mut in_head = false;
+    for event in events {
+        match event {
+            Event::Start(Tag::TableHead) => in_head = true,
+            Event::End(TagEnd::TableHead) => in_head = false,
+            Event::Start(Tag::TableRow) => rows.push(Vec::new()),
+            Event::Start(Tag::TableCell) => cell.clear(),
+            Event::End(TagEnd::TableCell) => {
+                let content = std::mem::take(&mut cell);
+                match rows.last_mut() {
+                    Some(row) if !in_head => row.push(content),
+                    _ => header.push(content),
+                }
+            }
+            Event::End(TagEnd::TableRow | TagEnd::Table) => {}
+            event => cell.push(event),
+        }
+    }
+
+    let mut list = vec![Event::Start(Tag::DefinitionList)];
+    for row in rows {
+        for (index, content) in row.into_iter().enumerate() {
+            list.push(Event::Start(Tag::DefinitionListTitle));
+            list.extend(header.get(index).into_iter().flatten().cloned());
+            list.push(Event::End(TagEnd::DefinitionListTitle));
+            list.push(Event::Start(Tag::DefinitionListDefinition));
+            list.extend(content);
+            list.push(Event::End(TagEnd::DefinitionListDefinition));
+        }
+    }
+    list.push(Event::End(TagEnd::DefinitionList));
+    list
+}
+
+/// Collects events up to and including the end tag matching an already consumed start tag
+fn collect_until_end<'a, I>(iter: &mut I) -> Vec<Event<'a>>
+where
+    I: Iterator<Item = Event<'a>>,
+{
+    let mut events = Vec::new();
+    let mut nest = 0;
+
+    for event in iter.by_ref() {
+        match event {
+            Event::Start(_) => nest += 1,
+            Event::End(_) if nest == 0 => {
+                events.push(event);
+                break;
+            }
+            Event::End(_) => nest -= 1,
+            _ => {}
+        }
+        events.push(event);
+    }
+    events
+}
+
+/// Checks whether a buffered block contains nothing but whitespace and line breaks
+fn is_empty_block(events: &[Event]) -> bool {
+    events.iter().all(|event| match event {
+        Event::Text(text) => text.trim().is_empty(),
+        Event::SoftBreak | Event::HardBreak | Event::End(_) => true,
+        _ => false,
+    })
+}
+
+/// Checks whether a buffered block holds a single image and nothing else but whitespace
+fn is_image_only(events: &[Event]) -> bool {
+    let is_blank = |event: &Event| match event {
+        Event::Text(text) => text.trim().is_empty(),
+        Event::SoftBreak => true,
+        _ => false,
+    };
+    // Drop the block's own end tag and surrounding whitespace
+    let events = match events.split_last() {
+        Some((Event::End(_), rest)) => rest,
+        _ => events,
+    };
+    let start = events
+        .iter()
+        .position(|e| !is_blank(e))
+        .unwrap_or(events.len());
+    let end = events
+        .iter()
+        .rposition(|e| !is_blank(e))
+        .map_or(start, |i| i + 1);
+    let content = &events[start..end];
+
+    if !matches!(content.first(), Some(Event::Start(Tag::Image { .. }))) {
+        return false;
+    }
+    let mut nest = 0;
+    for (index, event) in content.iter().enumerate() {
+        match event {
+            Event::Start(_) => nest += 1,
+            Event::End(_) => {
+                nest -= 1;
+                if nest == 0 {
+                    return index + 1 == content.len();
+                }
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Counts the items directly contained in a list, given the events following its start tag
+fn count_list_items(events: &[Event]) -> usize {
+    let mut nest = 0;
+    let mut count = 0;
+
+    for event in events {
+        match event {
+            Event::Start(Tag::Item) if nest == 0 => {
+                count += 1;
+                nest += 1;
+            }
+            Event::Start(_) => nest += 1,
+            Event::End(_) if nest == 0 => break,
+            Event::End(_) => nest -= 1,
+            _ => {}
+        }
+    }
+    count
+}
+
+/// Whether any item of a buffered list, excluding nested lists, is a task item
+fn contains_task_items(events: &[Event]) -> bool {
+    let mut nested_lists = 0;
+
+    for event in events {
+        match event {
+            Event::Start(Tag::List(_)) => nested_lists += 1,
+            Event::End(TagEnd::List(_)) if nested_lists == 0 => break,
+            Event::End(TagEnd::List(_)) => nested_lists -= 1,
+            Event::TaskListMarker(_) if nested_lists == 0 => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
 /// Renders markdown events to HTML and appends to the provided string
 ///
 /// # Arguments
@@ -193,6 +978,9 @@ where
 
 /// Renders markdown events to HTML using a fmt::Write implementation
 ///
+/// Pass `&mut writer` to keep using the writer afterwards, for example to
+/// append several documents to the same output.
+///
 /// # Arguments
 ///
 /// * `writer` - Any type implementing fmt::Write
@@ -210,6 +998,9 @@ where
 
 /// Renders markdown events to HTML using an io::Write implementation
 ///
+/// Pass `&mut writer` to keep using the writer afterwards, for example to
+/// append several documents to the same file.
+///
 /// # Arguments
 ///
 /// * `writer` - Any type implementing io::Write
@@ -225,10 +1016,201 @@ where
     renderer.run(iter)
 }
 
+/// Renders a Markdown document to UTF-8 encoded HTML bytes
+///
+/// A convenience over [`write_html_io`] for APIs that hand back bytes, such
+/// as HTTP response bodies.
+///
+/// # Arguments
+///
+/// * `markdown` - The Markdown source to render
+/// * `config` - Configuration for HTML rendering
+///
+/// # Example
+///
+/// ```rust
+/// use pulldown_html_ext::{render_bytes, HtmlConfig};
+///
+/// let bytes = render_bytes("Hello", &HtmlConfig::default()).unwrap();
+/// assert!(bytes.starts_with(b"<p>Hello</p>"));
+/// ```
+pub fn render_bytes(markdown: &str, config: &HtmlConfig) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+    let parser = Parser::new_ext(markdown, config.parser_options());
+    write_html_io(&mut output, parser, config)?;
+    Ok(output)
+}
+
 pub fn create_html_renderer<W: StrWrite, H: HtmlWriter<W>>(writer: H) -> HtmlRenderer<W, H> {
     HtmlRenderer::new(writer)
 }
 
+/// Computes the length in bytes of the HTML rendered for a Markdown document
+///
+/// The document goes through the full renderer, but the output is discarded
+/// rather than collected into a string.
+///
+/// # Arguments
+///
+/// * `markdown` - The Markdown source to measure
+/// * `config` - Configuration for HTML rendering
+///
+/// # Example
+///
+/// ```rust
+/// use pulldown_html_ext::{rendered_len, HtmlConfig};
+///
+/// let len = rendered_len("Hello", &HtmlConfig::default()).unwrap();
+/// assert_eq!(len, "<p>Hello</p>\n".len());
+/// ```
+pub fn rendered_len(markdown: &str, config: &HtmlConfig) -> Result<usize> {
+    let writer = DefaultHtmlWriter::new(CountingWriter::new(), config.clone());
+    let mut renderer = HtmlRenderer::new(writer);
+    renderer.run(Parser::new_ext(markdown, config.parser_options()))?;
+    Ok(renderer.writer.get_writer().count())
+}
+
+/// Renders a Markdown document, returning the HTML along with counts of the
+/// rendered elements
+///
+/// # Arguments
+///
+/// * `markdown` - The Markdown source to render
+/// * `config` - Configuration for HTML rendering
+///
+/// # Example
+///
+/// ```rust
+/// use pulldown_html_ext::{render_with_stats, HtmlConfig};
+///
+/// let (html, stats) = render_with_stats("# Hi\n\nTwo words", &HtmlConfig::default()).unwrap();
+/// assert!(html.contains("<h1"));
+/// assert_eq!(stats.headings, 1);
+/// assert_eq!(stats.words, 3);
+/// ```
+pub fn render_with_stats(markdown: &str, config: &HtmlConfig) -> Result<(String, RenderStats)> {
+    let mut output = String::new();
+    let writer = DefaultHtmlWriter::new(FmtWriter(&mut output), config.clone());
+    let mut renderer = HtmlRenderer::new(writer);
+    renderer.run(Parser::new_ext(markdown, config.parser_options()))?;
+    let stats = renderer.writer.get_state().stats;
+    Ok((output, stats))
+}
+
+/// Renders only the section of a document introduced by the given heading
+///
+/// The section starts at the heading whose ID matches `heading_id` and runs
+/// until the next heading of the same or a higher level. Explicit heading
+/// IDs (`{#id}`) are matched as well as the generated ones.
+///
+/// The part of the document before the section is rendered and discarded, so
+/// the section is rendered with the same state as in the whole document.
+///
+/// # Arguments
+///
+/// * `markdown` - The Markdown source to render
+/// * `heading_id` - ID of the heading that starts the section
+/// * `config` - Configuration for HTML rendering
+///
+/// # Example
+///
+/// ```rust
+/// use pulldown_html_ext::{render_section, HtmlConfig};
+///
+/// let markdown = "# Guide\n## Install {#install}\nRun it.\n## Usage\nUse it.";
+/// let html = render_section(markdown, "install", &HtmlConfig::default()).unwrap();
+/// assert!(html.contains("Run it."));
+/// assert!(!html.contains("Use it."));
+/// ```
+pub fn render_section(markdown: &str, heading_id: &str, config: &HtmlConfig) -> Result<String> {
+    // Find the heading among those of the whole document, so its ID is the rendered one
+    let headings = extract_headings(markdown, config)?;
+    let index = headings
+        .iter()
+        .position(|heading| heading.id.as_deref() == Some(heading_id));
+    let events: Vec<Event> = Parser::new_ext(markdown, config.parser_options()).collect();
+    let section = index.and_then(|index| {
+        events
+            .iter()
+            .enumerate()
+            .filter(|(_, event)| matches!(event, Event::Start(Tag::Heading { .. })))
+            .nth(index)
+            .map(|(start, _)| (start, headings[index].level))
+    });
+
+    let Some((start, level)) = section else {
+        return Err(HtmlError::Render(format!(
+            "no heading with id '{}' found",
+            heading_id
+        )));
+    };
+    let end = events[start + 1..]
+        .iter()
+        .position(|event| {
+            matches!(event, Event::Start(Tag::Heading { level: next, .. }) if *next <= level)
+        })
+        .map_or(events.len(), |offset| start + 1 + offset);
+
+    // Render the document up to the section with the output discarded, and carry
+    // the state forward so IDs and numbering match whole-document rendering
+    let mut discarded = String::new();
+    let writer = DefaultHtmlWriter::new(FmtWriter(&mut discarded), config.clone());
+    let mut renderer = HtmlRenderer::new(writer);
+    renderer.run(events[..start].iter().cloned())?;
+    let state = std::mem::take(renderer.writer.get_state());
+
+    let mut output = String::new();
+    let mut writer = DefaultHtmlWriter::new(FmtWriter(&mut output), config.clone());
+    *writer.get_state() = state;
+    HtmlRenderer::new(writer).run(events[start..end].iter().cloned())?;
+    Ok(output)
+}
+
+/// Renders the excerpt of a document, up to its first thematic break or
+/// excerpt marker comment
+///
+/// The marker is configured by `html.excerpt_marker`. Elements left open
+/// where the excerpt ends are closed. Without a break or marker, the whole
+/// document is rendered.
+///
+/// # Arguments
+///
+/// * `markdown` - The Markdown source to render
+/// * `config` - Configuration for HTML rendering
+///
+/// # Example
+///
+/// ```rust
+/// use pulldown_html_ext::{render_excerpt, HtmlConfig};
+///
+/// let markdown = "Teaser.\n\n<!-- more -->\n\nThe rest.";
+/// let html = render_excerpt(markdown, &HtmlConfig::default()).unwrap();
+/// assert!(html.contains("Teaser."));
+/// assert!(!html.contains("The rest."));
+/// ```
+pub fn render_excerpt(markdown: &str, config: &HtmlConfig) -> Result<String> {
+    let marker = config.html.excerpt_marker.trim();
+    let mut events = Vec::new();
+    let mut open = Vec::new();
+    for event in Parser::new_ext(markdown, config.parser_options()) {
+        match &event {
+            Event::Rule => break,
+            Event::Html(html) | Event::InlineHtml(html) if html.trim() == marker => break,
+            Event::Start(tag) => open.push(tag.to_end()),
+            Event::End(_) => {
+                open.pop();
+            }
+            _ => {}
+        }
+        events.push(event);
+    }
+    events.extend(open.into_iter().rev().map(Event::End));
+
+    let mut output = String::new();
+    push_html(&mut output, events.into_iter(), config)?;
+    Ok(output)
+}
+
 #[cfg(test)]
 mod tests_mod {
     use super::*;
@@ -278,6 +1260,192 @@ mod tests_mod {
         assert_html_eq!(result, r#"<h1 id="heading-1">Test</h1>"#);
     }
 
+    #[test]
+    fn test_write_html_io_repeatedly() {
+        let mut output = Vec::new();
+        let config = HtmlConfig::default();
+
+        write_html_io(&mut output, Parser::new("# First"), &config).unwrap();
+        write_html_io(&mut output, Parser::new("# Second"), &config).unwrap();
+
+        let result = String::from_utf8(output).unwrap();
+        assert!(result.contains(r#"<h1 id="heading-1">First</h1>"#));
+        assert!(result.contains(r#"<h1 id="heading-1">Second</h1>"#));
+    }
+
+    #[test]
+    fn test_render_bytes() {
+        let markdown = "# Café\n\n* **one**\n* two";
+        let config = HtmlConfig::default();
+
+        let mut expected = String::new();
+        push_html(&mut expected, Parser::new(markdown), &config).unwrap();
+
+        let bytes = render_bytes(markdown, &config).unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_write_html_fmt_repeatedly() {
+        let mut output = String::from("<main>");
+        let config = HtmlConfig::default();
+
+        write_html_fmt(&mut output, Parser::new("One"), &config).unwrap();
+        write_html_fmt(&mut output, Parser::new("Two"), &config).unwrap();
+        output.push_str("</main>");
+
+        assert_html_eq!(output, "<main><p>One</p><p>Two</p></main>");
+    }
+
+    #[test]
+    fn test_block_context() {
+        struct ContextWriter<W: StrWrite> {
+            writer: W,
+            config: HtmlConfig,
+            state: HtmlState,
+            contexts: Vec<Option<BlockContext>>,
+        }
+
+        impl<W: StrWrite> HtmlWriter<W> for ContextWriter<W> {
+            fn get_writer(&mut self) -> &mut W {
+                &mut self.writer
+            }
+
+            fn get_config(&self) -> &HtmlConfig {
+                &self.config
+            }
+
+            fn get_state(&mut self) -> &mut HtmlState {
+                &mut self.state
+            }
+
+            fn text(&mut self, text: &str) -> std::result::Result<(), HtmlError> {
+                let context = self.state.current_block_context();
+                self.contexts.push(context);
+                self.write_str(text)
+            }
+        }
+
+        let mut output = String::new();
+        let writer = ContextWriter {
+            writer: FmtWriter(&mut output),
+            config: HtmlConfig::default(),
+            state: HtmlState::new(),
+            contexts: Vec::new(),
+        };
+        let mut renderer = HtmlRenderer::new(writer);
+        renderer
+            .run(Parser::new("- text\n\n> quoted\n\nplain"))
+            .unwrap();
+
+        assert_eq!(
+            renderer.writer.contexts,
+            [
+                Some(BlockContext::ListItem),
+                Some(BlockContext::BlockQuote),
+                None
+            ]
+        );
+        assert!(renderer.writer.state.block_contexts.is_empty());
+    }
+
+    #[test]
+    fn test_render_section() {
+        let markdown = "# Guide\n\nIntro text.\n\n\
+                        ## Install {#install}\n\nRun the installer.\n\n\
+                        ### Requirements\n\nA computer.\n\n\
+                        ## Usage\n\nRun the program.";
+        let config = HtmlConfig::default();
+
+        let output = render_section(markdown, "install", &config).unwrap();
+
+        assert_html_eq!(
+            output,
+            "<h2 id=\"install\">Install</h2><p>Run the installer.</p>\
+             <h3 id=\"heading-3\">Requirements</h3><p>A computer.</p>"
+        );
+    }
+
+    #[test]
+    fn test_render_section_content_ids() {
+        let markdown = "# Intro\n\n## Setup\n\nFirst.\n\n## Setup\n\nSecond.";
+        let mut config = HtmlConfig::default();
+        config.elements.headings.id_prefix = String::new();
+        config.elements.headings.id_from_content = true;
+        config.elements.headings.on_duplicate_id = DupPolicy::Suffix;
+
+        let output = render_section(markdown, "setup-1", &config).unwrap();
+
+        assert_html_eq!(output, "<h2 id=\"setup-1\">Setup</h2><p>Second.</p>");
+    }
+
+    #[test]
+    fn test_render_section_matches_document_state() {
+        let markdown = "# A\n\n## B\n\nText.";
+        let mut config = HtmlConfig::default();
+        config.html.pretty_print = false;
+        config.elements.headings.id_prefix = String::new();
+        config.elements.headings.id_from_content = true;
+        config.elements.headings.auto_number = true;
+
+        // Section numbers continue from the headings before the section
+        let output = render_section(markdown, "b", &config).unwrap();
+        assert_eq!(output, "<h2 id=\"b\">1.1. B</h2><p>Text.</p>");
+
+        // IDs taken by other elements before the section are accounted for
+        config.elements.headings.auto_number = false;
+        config.elements.headings.on_duplicate_id = DupPolicy::Suffix;
+        config.hooks.id_generator = Some(std::sync::Arc::new(|tag: &str| {
+            (tag == "p").then(|| "install".to_string())
+        }));
+        let markdown = "Intro.\n\n# Install\n\nRun it.";
+        assert!(render_section(markdown, "install", &config).is_err());
+        let output = render_section(markdown, "install-1", &config).unwrap();
+        assert!(output.starts_with("<h1 id=\"install-1\">Install</h1>"));
+    }
+
+    #[test]
+    fn test_render_section_missing_heading() {
+        let config = HtmlConfig::default();
+        let result = render_section("# Title\n\nText", "missing", &config);
+        assert!(matches!(result, Err(HtmlError::Render(_))));
+    }
+
+    #[test]
+    fn test_entry_points_share_parser_options() {
+        let markdown = "---\ntitle: Post\n---\n\n# Post {#post}\n\nBody.";
+        let mut config = HtmlConfig::default();
+        config.html.pretty_print = false;
+        let expected = "<h1 id=\"post\">Post</h1><p>Body.</p>";
+
+        let bytes = render_bytes(markdown, &config).unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap(), expected);
+        assert_eq!(render_with_stats(markdown, &config).unwrap().0, expected);
+        assert_eq!(render_excerpt(markdown, &config).unwrap(), expected);
+        assert_eq!(render_section(markdown, "post", &config).unwrap(), expected);
+        assert_eq!(
+            render_with_frontmatter(markdown, &config).unwrap().1,
+            expected
+        );
+        assert_eq!(rendered_len(markdown, &config).unwrap(), expected.len());
+    }
+
+    #[test]
+    fn test_render_excerpt() {
+        let mut config = HtmlConfig::default();
+        config.html.pretty_print = false;
+
+        let output = render_excerpt("# Post\n\nTeaser.\n\n---\n\nThe rest.", &config).unwrap();
+        assert_eq!(output, "<h1 id=\"heading-1\">Post</h1><p>Teaser.</p>");
+
+        // An inline marker closes the elements it interrupts
+        let output = render_excerpt("> *Teaser <!-- more --> rest*\n\nMore.", &config).unwrap();
+        assert_eq!(output, "<blockquote><p><em>Teaser </em></p></blockquote>");
+
+        let output = render_excerpt("No marker.", &config).unwrap();
+        assert_eq!(output, "<p>No marker.</p>");
+    }
+
     #[test]
     fn test_with_syntax_highlighting() {
         let markdown = "```rust\nfn main() {\n    println!(\"Hello\");\n}\n```";
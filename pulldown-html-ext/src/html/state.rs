@@ -1,4 +1,7 @@
-use pulldown_cmark::{Alignment, LinkType};
+use pulldown_cmark::{Alignment, Event, LinkType, TagEnd};
+use std::collections::HashSet;
+
+use crate::html::headings::HeadingInfo;
 
 /// Represents the current state of table parsing
 #[derive(Copy, Clone, Debug, PartialEq, Default)]
@@ -22,6 +25,72 @@ pub enum ListContext {
     Unordered,
 }
 
+/// A container block that other blocks can be nested in
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BlockContext {
+    /// A block quote (<blockquote>)
+    BlockQuote,
+    /// A list item (<li>)
+    ListItem,
+    /// A table cell (<td> or <th>)
+    TableCell,
+    /// A footnote definition
+    FootnoteDefinition,
+    /// A definition list term (<dt>)
+    DefinitionListTitle,
+    /// A definition list definition (<dd>)
+    DefinitionListDefinition,
+}
+
+/// Counts of the elements rendered in a document
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// Number of headings
+    pub headings: usize,
+    /// Number of links
+    pub links: usize,
+    /// Number of images
+    pub images: usize,
+    /// Number of code blocks
+    pub code_blocks: usize,
+    /// Number of words of text, excluding code blocks
+    pub words: usize,
+}
+
+/// Heading IDs already used in a document, for keeping generated IDs unique
+#[derive(Clone, Debug, Default)]
+pub struct HeadingIds {
+    used: HashSet<String>,
+}
+
+impl HeadingIds {
+    /// Record an ID, suffixing generated IDs with `-1`, `-2`, ... if already used
+    ///
+    /// Explicit IDs are recorded as given.
+    pub fn assign(&mut self, id: String, generated: bool) -> String {
+        let id = if generated && self.used.contains(&id) {
+            (1..)
+                .map(|n| format!("{}-{}", id, n))
+                .find(|candidate| !self.used.contains(candidate))
+                .unwrap()
+        } else {
+            id
+        };
+        self.used.insert(id.clone());
+        id
+    }
+
+    /// Whether an ID has been recorded
+    pub fn contains(&self, id: &str) -> bool {
+        self.used.contains(id)
+    }
+
+    /// Forget all recorded IDs
+    pub fn clear(&mut self) {
+        self.used.clear();
+    }
+}
+
 /// Maintains the state of the HTML rendering process
 pub struct HtmlState {
     /// Stack for tracking list numbers in ordered lists
@@ -34,14 +103,70 @@ pub struct HtmlState {
     pub table_alignments: Vec<Alignment>,
     /// Stack for tracking nested lists
     pub list_stack: Vec<ListContext>,
+    /// Index of the current item and total item count for each open list whose
+    /// length was determined ahead of rendering
+    pub list_item_positions: Vec<(usize, usize)>,
+    /// Whether the list about to be started contains task items
+    pub list_contains_tasks: bool,
+    /// For each open list item, whether a task label was opened in it
+    pub task_item_labels: Vec<bool>,
+    /// Destinations and titles of the reference-style links seen so far, for the reference index
+    pub reference_links: Vec<(String, String)>,
+    /// Number of open emphasis and strong emphasis elements
+    pub emphasis_depth: usize,
     /// Stack for tracking nested links
     pub link_stack: Vec<LinkType>,
+    /// Stack of the container blocks enclosing the current position
+    pub block_contexts: Vec<BlockContext>,
+    /// Stack of the end tags expected for the open tags, when checking nesting
+    /// or wrapping loose text
+    pub open_tags: Vec<TagEnd>,
     /// Stack for tracking heading IDs
     pub heading_stack: Vec<String>,
+    /// Plain text of the heading about to be started, when known ahead of rendering
+    pub heading_text: String,
+    /// Plain text of the definition list term about to be started, when known ahead of rendering
+    pub term_text: String,
+    /// Heading IDs emitted so far
+    pub heading_ids: HeadingIds,
+    /// Headings rendered so far, when they are being collected
+    pub collected_headings: Option<Vec<HeadingInfo>>,
+    /// Number of headings seen at each level, for section numbering
+    pub heading_counters: Vec<u32>,
     /// Whether currently processing a code block
     pub currently_in_code_block: bool,
+    /// Text of the code block currently being processed
+    pub code_buffer: String,
+    /// Language of the code block currently being processed
+    pub code_block_language: Option<String>,
+    /// Whether the current code block was opened as a bare `<pre>`
+    pub code_block_bare: bool,
+    /// Number of the first line of the current code block
+    pub code_block_start_line: usize,
+    /// Whether an image has been rendered
+    pub image_seen: bool,
+    /// Number of thematic breaks rendered so far
+    pub rule_count: usize,
+    /// Whether the document headline has been marked
+    pub headline_written: bool,
+    /// Whether a top-level paragraph has been started
+    pub seen_first_paragraph: bool,
+    /// Whether currently processing a paragraph
+    pub currently_in_paragraph: bool,
+    /// Whether the paragraph about to be started holds only an image
+    pub paragraph_image_only: bool,
+    /// Whether currently processing an inline code span
+    pub currently_in_inline_code: bool,
     /// Whether currently processing a footnote definition
     pub currently_in_footnote: bool,
+    /// Events of footnote definitions held back for the end of the document
+    pub deferred_footnotes: Vec<Event<'static>>,
+    /// Whether the deferred footnote definitions are being rendered
+    pub flushing_footnotes: bool,
+    /// Counts of the elements rendered so far
+    pub stats: RenderStats,
+    /// Number of documents rendered so far by the writer
+    pub documents_rendered: usize,
 }
 
 impl HtmlState {
@@ -53,10 +178,37 @@ impl HtmlState {
             table_cell_index: 0,
             table_alignments: Vec::new(),
             list_stack: Vec::new(),
+            list_item_positions: Vec::new(),
+            list_contains_tasks: false,
+            task_item_labels: Vec::new(),
+            reference_links: Vec::new(),
+            emphasis_depth: 0,
             link_stack: Vec::new(),
+            block_contexts: Vec::new(),
+            open_tags: Vec::new(),
             heading_stack: Vec::new(),
+            heading_text: String::new(),
+            term_text: String::new(),
+            heading_ids: HeadingIds::default(),
+            collected_headings: None,
+            heading_counters: Vec::new(),
             currently_in_code_block: false,
+            code_buffer: String::new(),
+            code_block_language: None,
+            code_block_bare: false,
+            code_block_start_line: 1,
+            image_seen: false,
+            rule_count: 0,
+            headline_written: false,
+            seen_first_paragraph: false,
+            currently_in_paragraph: false,
+            paragraph_image_only: false,
+            currently_in_inline_code: false,
             currently_in_footnote: false,
+            deferred_footnotes: Vec::new(),
+            flushing_footnotes: false,
+            stats: RenderStats::default(),
+            documents_rendered: 0,
         }
     }
 
@@ -68,9 +220,36 @@ impl HtmlState {
         self.table_cell_index = 0;
         self.table_alignments.clear();
         self.list_stack.clear();
+        self.list_item_positions.clear();
+        self.list_contains_tasks = false;
+        self.task_item_labels.clear();
+        self.reference_links.clear();
+        self.emphasis_depth = 0;
         self.link_stack.clear();
+        self.block_contexts.clear();
+        self.open_tags.clear();
         self.heading_stack.clear();
+        self.heading_text.clear();
+        self.term_text.clear();
+        self.heading_ids.clear();
+        self.collected_headings = None;
+        self.heading_counters.clear();
         self.currently_in_code_block = false;
+        self.code_buffer.clear();
+        self.code_block_language = None;
+        self.code_block_bare = false;
+        self.code_block_start_line = 1;
+        self.image_seen = false;
+        self.rule_count = 0;
+        self.headline_written = false;
+        self.seen_first_paragraph = false;
+        self.currently_in_paragraph = false;
+        self.paragraph_image_only = false;
+        self.currently_in_inline_code = false;
+        self.deferred_footnotes.clear();
+        self.flushing_footnotes = false;
+        self.stats = RenderStats::default();
+        self.documents_rendered = 0;
     }
 
     #[allow(dead_code)]
@@ -85,7 +264,6 @@ impl HtmlState {
         self.table_state == TableContext::InHeader
     }
 
-    #[allow(dead_code)]
     /// Get the current nesting level of lists
     pub fn list_depth(&self) -> usize {
         self.list_stack.len()
@@ -96,6 +274,11 @@ impl HtmlState {
     pub fn current_list_type(&self) -> Option<ListContext> {
         self.list_stack.last().copied()
     }
+
+    /// Get the innermost container block, if any
+    pub fn current_block_context(&self) -> Option<BlockContext> {
+        self.block_contexts.last().copied()
+    }
 }
 
 impl Default for HtmlState {
@@ -121,6 +304,17 @@ mod tests {
         assert!(!state.currently_in_code_block);
     }
 
+    #[test]
+    fn test_heading_ids_assign() {
+        let mut ids = HeadingIds::default();
+        assert_eq!(ids.assign("intro".to_string(), true), "intro");
+        assert_eq!(ids.assign("intro".to_string(), true), "intro-1");
+        assert_eq!(ids.assign("intro-2".to_string(), false), "intro-2");
+        assert_eq!(ids.assign("intro".to_string(), true), "intro-3");
+        // Explicit IDs are kept even when already used
+        assert_eq!(ids.assign("intro".to_string(), false), "intro");
+    }
+
     #[test]
     fn test_renderer_state_reset() {
         let mut state = HtmlState::new();
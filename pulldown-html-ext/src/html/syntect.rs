@@ -1,7 +1,9 @@
 use crate::html::{config, HtmlError};
+use crate::utils::{parse_fence_info, FenceInfo};
 use lazy_static::lazy_static;
 use pulldown_cmark_escape::StrWrite;
 use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
 use syntect::highlighting::{Theme, ThemeSet};
 use syntect::html::{ClassStyle, ClassedHTMLGenerator};
 use syntect::parsing::SyntaxSet;
@@ -47,6 +49,35 @@ pub struct SyntectConfigStyle {
     /// Whether to include CSS in the output
     #[serde(default = "default_inject_css")]
     pub inject_css: bool,
+    /// How the CSS is included when `inject_css` is enabled
+    #[serde(default)]
+    pub css_mode: CssMode,
+    /// Themes to use for specific languages instead of `theme`, keyed by the
+    /// language name after alias resolution
+    #[serde(default)]
+    pub theme_by_language: HashMap<String, String>,
+    /// Class of a container wrapped around each highlighted block, under
+    /// which the generated CSS is scoped so it cannot clash with page styles
+    #[serde(default)]
+    pub scope_class: Option<String>,
+    /// Language used to highlight all inline code spans; inline code is not
+    /// highlighted when unset
+    #[serde(default)]
+    pub inline_language: Option<String>,
+}
+
+/// How generated theme CSS is included in highlighted output
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CssMode {
+    /// Inline the CSS in a `<style>` block
+    #[default]
+    Inline,
+    /// Emit a `<link rel="stylesheet">` pointing at the given href, leaving
+    /// the CSS to be written out by the caller
+    Link(String),
+    /// Do not include any CSS
+    None,
 }
 
 fn default_class_style() -> ClassStyle {
@@ -76,6 +107,10 @@ impl Default for SyntectConfigStyle {
             theme: "base16-ocean.dark".to_string(),
             class_style: ClassStyle::Spaced,
             inject_css: true,
+            css_mode: CssMode::Inline,
+            theme_by_language: HashMap::new(),
+            scope_class: None,
+            inline_language: None,
         }
     }
 }
@@ -150,19 +185,46 @@ impl<'a, W: StrWrite> SyntectWriter<'a, W> {
         html_generator.finalize()
     }
 
-    fn get_theme(&self) -> Result<&Theme, String> {
+    /// The language to highlight the current inline code span in, if any
+    fn inline_language(&mut self) -> Option<String> {
+        if !self.get_state().currently_in_inline_code {
+            return None;
+        }
+        let lang = self.style.inline_language.as_deref()?;
+        Some(self.resolve_language(lang))
+    }
+
+    fn get_theme(&self, name: &str) -> Result<&Theme, String> {
         let theme_set = self.theme_set.unwrap_or(&THEME_SET);
         theme_set
             .themes
-            .get(&self.style.theme)
-            .ok_or_else(|| format!("Theme '{}' not found", self.style.theme))
+            .get(name)
+            .ok_or_else(|| format!("Theme '{}' not found", name))
     }
 
-    pub fn get_theme_css(&self) -> Result<String, String> {
-        let theme = self.get_theme()?;
+    fn theme_css(&self, name: &str) -> Result<String, String> {
+        let theme = self.get_theme(name)?;
         syntect::html::css_for_theme_with_class_style(theme, self.style.class_style)
             .map_err(|e| e.to_string())
     }
+
+    /// CSS for the global theme, followed by the CSS of each language-specific
+    /// theme scoped to code blocks in that language
+    pub fn get_theme_css(&self) -> Result<String, String> {
+        let mut css = self.theme_css(&self.style.theme)?;
+
+        let mut languages: Vec<_> = self.style.theme_by_language.iter().collect();
+        languages.sort();
+        for (lang, theme) in languages {
+            let scope = format!(".{}", self.language_class(lang));
+            css.push_str(&scope_css(&self.theme_css(theme)?, &scope));
+        }
+
+        if let Some(scope_class) = &self.style.scope_class {
+            css = scope_css(&css, &format!(".{}", scope_class));
+        }
+        Ok(css)
+    }
 }
 
 impl<'a, W: StrWrite> HtmlWriter<W> for SyntectWriter<'a, W> {
@@ -179,35 +241,35 @@ impl<'a, W: StrWrite> HtmlWriter<W> for SyntectWriter<'a, W> {
     }
 
     fn start_code_block(&mut self, kind: pulldown_cmark::CodeBlockKind) -> Result<(), HtmlError> {
-        self.current_lang = match kind {
-            pulldown_cmark::CodeBlockKind::Fenced(ref info) => {
-                if info.is_empty() {
-                    None
-                } else {
-                    Some(info.to_string())
-                }
-            }
-            _ => None,
+        let info = match kind {
+            pulldown_cmark::CodeBlockKind::Fenced(ref info) => parse_fence_info(info),
+            pulldown_cmark::CodeBlockKind::Indented => FenceInfo::default(),
         };
-
-        self.write_str("<pre")?;
-        self.write_attributes("pre")?;
-        self.write_str("><code")?;
-
-        if let Some(ref lang) = self.current_lang {
-            self.write_str(&format!(" class=\"language-{}\"", lang))?;
+        self.current_lang = info
+            .language
+            .as_deref()
+            .map(|lang| self.resolve_language(lang));
+
+        let lang = self.current_lang.clone();
+        self.start_code_block_wrapper()?;
+        if let Some(scope_class) = self.style.scope_class.clone() {
+            self.write_str("<div")?;
+            self.write_attribute("class", &scope_class)?;
+            self.write_str(">")?;
         }
+        self.write_code_block_open(lang.as_deref(), &info)?;
 
-        self.write_attributes("code")?;
-        self.write_str(">")?;
-
+        self.get_state().code_block_language = self.current_lang.clone();
         self.get_state().currently_in_code_block = true;
         Ok(())
     }
 
     fn text(&mut self, text: &str) -> Result<(), HtmlError> {
         if self.get_state().currently_in_code_block {
-            let highlighted = self.highlight_code(text, self.current_lang.as_deref());
+            self.get_state().code_buffer.push_str(text);
+            Ok(())
+        } else if let Some(lang) = self.inline_language() {
+            let highlighted = self.highlight_code(text, Some(&lang));
             self.write_str(&highlighted)
         } else {
             self.inner.text(text)
@@ -215,20 +277,100 @@ impl<'a, W: StrWrite> HtmlWriter<W> for SyntectWriter<'a, W> {
     }
 
     fn end_code_block(&mut self) -> Result<(), HtmlError> {
-        self.write_str("</code></pre>")?;
+        // Highlight the whole block at once, so multi-line constructs are recognized
+        let code = self.take_code_text();
+        if self.get_state().code_block_bare {
+            // Bare blocks hold pre-formatted text rather than code
+            self.write_code_text(&code)?;
+        } else {
+            let highlighted = self.highlight_code(&code, self.current_lang.as_deref());
+            if self.get_config().elements.code_blocks.line_numbers {
+                // Closing tags may follow the last newline, so only the source lines are numbered
+                let start = self.get_state().code_block_start_line;
+                let line_count = code.split_inclusive('\n').count();
+                for (index, line) in highlighted.split_inclusive('\n').enumerate() {
+                    if index < line_count {
+                        self.write_line_number(start.saturating_add(index))?;
+                    }
+                    self.write_str(line)?;
+                }
+            } else {
+                self.write_str(&highlighted)?;
+            }
+        }
+        self.write_code_block_close()?;
+        if self.style.scope_class.is_some() {
+            self.write_str("</div>")?;
+        }
+        self.end_code_block_wrapper()?;
         self.current_lang = None;
         self.get_state().currently_in_code_block = false;
         Ok(())
     }
 }
 
+/// Prefixes every selector of a stylesheet with a scoping selector
+fn scope_css(css: &str, scope: &str) -> String {
+    let mut scoped = String::with_capacity(css.len());
+    for line in LinesWithEndings::from(css) {
+        match line.trim_end().strip_suffix('{') {
+            Some(selectors) if !line.trim_start().starts_with('@') => {
+                let selectors: Vec<_> = selectors
+                    .split(',')
+                    .map(|selector| format!("{} {}", scope, selector.trim()))
+                    .collect();
+                scoped.push_str(&selectors.join(", "));
+                scoped.push_str(" {\n");
+            }
+            _ => scoped.push_str(line),
+        }
+    }
+    scoped
+}
+
+/// Highlighted HTML output along with the theme CSS it references
+#[derive(Clone, Debug, Default)]
+pub struct HighlightedHtml {
+    html: String,
+    css: Option<String>,
+}
+
+impl HighlightedHtml {
+    /// The rendered HTML
+    pub fn html(&self) -> &str {
+        &self.html
+    }
+
+    /// The theme CSS, when it was not inlined into the HTML
+    ///
+    /// Only set for [`CssMode::Link`], so the caller can write the stylesheet
+    /// to the linked location.
+    pub fn css(&self) -> Option<&str> {
+        self.css.as_deref()
+    }
+
+    /// Consume the output, returning only the HTML
+    pub fn into_html(self) -> String {
+        self.html
+    }
+}
+
 /// Convenience function to render Markdown with syntax highlighting
 pub fn push_html_with_highlighting(
     markdown: &str,
     config: &HtmlConfig,
 ) -> Result<String, HtmlError> {
+    render_with_highlighting(markdown, config).map(HighlightedHtml::into_html)
+}
+
+/// Render Markdown with syntax highlighting, returning the HTML and any
+/// separately generated theme CSS
+pub fn render_with_highlighting(
+    markdown: &str,
+    config: &HtmlConfig,
+) -> Result<HighlightedHtml, HtmlError> {
     use pulldown_cmark::Parser;
-    use pulldown_cmark_escape::FmtWriter;
+    use pulldown_cmark_escape::{escape_href, FmtWriter};
 
     let mut output = String::new();
     let writer = SyntectWriter::new(FmtWriter(&mut output), config);
@@ -239,13 +381,34 @@ pub fn push_html_with_highlighting(
 
     // Add CSS if configured
     if let Some(ref style) = config.syntect {
-        if style.inject_css {
+        if style.inject_css && style.css_mode != CssMode::None {
+            let newline = config.html.line_ending.as_str();
             match renderer.writer.get_theme_css() {
-                Ok(css) => return Ok(format!("<style>{}</style>\n{}", css, output)),
+                Ok(css) => {
+                    return Ok(match style.css_mode {
+                        CssMode::Link(ref href) => {
+                            let mut link = String::from("<link rel=\"stylesheet\" href=\"");
+                            escape_href(&mut link, href)?;
+                            link.push_str("\">");
+                            link.push_str(newline);
+                            HighlightedHtml {
+                                html: link + &output,
+                                css: Some(css),
+                            }
+                        }
+                        _ => HighlightedHtml {
+                            html: format!("<style>{}</style>{}{}", css, newline, output),
+                            css: None,
+                        },
+                    });
+                }
                 Err(e) => eprintln!("Failed to generate syntax highlighting CSS: {}", e),
             }
         }
     }
 
-    Ok(output)
+    Ok(HighlightedHtml {
+        html: output,
+        css: None,
+    })
 }
@@ -1,6 +1,14 @@
 use super::{ListContext, TableContext};
+use crate::html::config::{AttrQuote, EmojiMode, HrRender, UnknownPolicy};
+use crate::html::headings::HeadingInfo;
+use crate::html::hooks::ElementInfo;
 use crate::html::state::HtmlState;
 use crate::html::HtmlError;
+use crate::utils::{
+    encode_non_ascii, escape_html_keep_entities, find_bare_urls, find_emoji_shortcodes,
+    find_iso_dates, find_sup_sub, join_classes, normalize_whitespace, parse_fence_info,
+    sanitize_id, split_title_dimensions, trim_blank_lines, FenceInfo,
+};
 use crate::HtmlConfig;
 
 use pulldown_cmark::{
@@ -20,20 +28,142 @@ pub trait HtmlWriter<W: StrWrite> {
 
     /// Write HTML attributes for a given element
     fn write_attributes(&mut self, element: &str) -> Result<(), HtmlError> {
-        let mut attrs_string = String::new();
+        self.write_element_attributes(&ElementInfo::new(element))
+    }
 
-        if let Some(attrs) = self.get_config().attributes.element_attributes.get(element) {
-            for (key, value) in attrs {
-                attrs_string.push_str(&format!(" {}=\"{}\"", key, value));
-            }
+    /// Write HTML attributes for a given element, merging the given classes
+    /// with the configured ones into a single `class` attribute
+    fn write_attributes_with_classes<'c, I>(
+        &mut self,
+        element: &str,
+        classes: I,
+    ) -> Result<(), HtmlError>
+    where
+        I: IntoIterator<Item = &'c str>,
+    {
+        self.write_element_attributes_with_classes(&ElementInfo::new(element), classes)
+    }
+
+    /// Write the `id` from the ID generator hook, kept unique within the document
+    fn write_generated_id(&mut self, element: &str) -> Result<(), HtmlError> {
+        let id = match &self.get_config().hooks.id_generator {
+            Some(generator) => generator(element),
+            None => None,
+        };
+        if let Some(id) = id {
+            let id = self.get_state().heading_ids.assign(id, true);
+            self.write_attribute("id", &id)?;
         }
+        Ok(())
+    }
+
+    /// Write the configured static and provider attributes for an element
+    fn write_element_attributes(&mut self, info: &ElementInfo) -> Result<(), HtmlError> {
+        self.write_element_attributes_with_classes(info, None)
+    }
 
-        if !attrs_string.is_empty() {
-            self.write_str(&attrs_string)?;
+    /// Write the configured static and provider attributes for an element, with
+    /// the given classes and the configured ones merged into a single `class` attribute
+    fn write_element_attributes_with_classes<'c, I>(
+        &mut self,
+        info: &ElementInfo,
+        classes: I,
+    ) -> Result<(), HtmlError>
+    where
+        I: IntoIterator<Item = &'c str>,
+    {
+        let (configured, others): (Vec<_>, Vec<_>) = self
+            .element_attributes(info)
+            .into_iter()
+            .partition(|(key, _)| key == "class");
+        let mut names: Vec<&str> = classes.into_iter().collect();
+        names.extend(configured.iter().map(|(_, value)| value.as_str()));
+        let class = join_classes(names, self.get_config().attributes.dedupe_classes);
+        if !class.is_empty() {
+            self.write_attribute("class", &class)?;
+        }
+        for (key, value) in others {
+            self.write_attribute(&key, &value)?;
         }
         Ok(())
     }
 
+    /// The configured static and provider attributes for an element
+    fn element_attributes(&self, info: &ElementInfo) -> Vec<(String, String)> {
+        let is_block = matches!(
+            info.tag,
+            "p" | "h1"
+                | "h2"
+                | "h3"
+                | "h4"
+                | "h5"
+                | "h6"
+                | "ul"
+                | "ol"
+                | "li"
+                | "blockquote"
+                | "pre"
+                | "table"
+        );
+        let mut attrs: Vec<(String, String)> = Vec::new();
+        if is_block {
+            attrs.extend(
+                self.get_config()
+                    .html
+                    .global_block_attributes
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.clone())),
+            );
+            attrs.sort();
+        }
+        if let Some(element_attrs) = self
+            .get_config()
+            .attributes
+            .element_attributes
+            .get(info.tag)
+        {
+            merge_attributes(
+                &mut attrs,
+                element_attrs.iter().map(|(k, v)| (k.clone(), v.clone())),
+            );
+        }
+        if let Some(provider) = &self.get_config().hooks.attribute_provider {
+            merge_attributes(&mut attrs, provider(info.tag, info));
+        }
+        attrs
+    }
+
+    /// Write a single ` name="value"` attribute, escaping the value
+    fn write_attribute(&mut self, name: &str, value: &str) -> Result<(), HtmlError> {
+        self.write_str(" ")?;
+        self.write_str(name)?;
+        self.write_str("=")?;
+        self.write_quoted(value)
+    }
+
+    /// Write a ` name="url"` attribute, escaping the value as a URL
+    fn write_href_attribute(&mut self, name: &str, url: &str) -> Result<(), HtmlError> {
+        let quote = self.get_config().html.attribute_quote.as_str();
+        self.write_str(" ")?;
+        self.write_str(name)?;
+        self.write_str("=")?;
+        self.write_str(quote)?;
+        escape_href(self.get_writer(), url).map_err(|_| HtmlError::Write(std::fmt::Error))?;
+        self.write_str(quote)
+    }
+
+    /// Write an attribute value surrounded by the configured quote character
+    fn write_quoted(&mut self, value: &str) -> Result<(), HtmlError> {
+        let quote = self.get_config().html.attribute_quote;
+        self.write_str(quote.as_str())?;
+        match quote {
+            AttrQuote::Double => escape_html(self.get_writer(), value)
+                .map_err(|_| HtmlError::Write(std::fmt::Error))?,
+            AttrQuote::Single => self.write_str(&escape_single_quoted(value))?,
+        }
+        self.write_str(quote.as_str())
+    }
+
     fn get_config(&self) -> &HtmlConfig;
 
     fn get_writer(&mut self) -> &mut W;
@@ -45,16 +175,89 @@ pub trait HtmlWriter<W: StrWrite> {
         url.starts_with("http://") || url.starts_with("https://")
     }
 
+    /// Prepare a link or image title for output, normalizing whitespace if configured
+    fn format_title(&self, title: &str) -> String {
+        if self.get_config().elements.links.normalize_titles {
+            normalize_whitespace(title)
+        } else {
+            title.to_string()
+        }
+    }
+
+    /// Called before the first event of a document
+    fn start_document(&mut self) -> Result<(), HtmlError> {
+        if self.get_config().html.schema_org {
+            self.write_str("<article")?;
+            self.write_str(" itemscope")?;
+            self.write_attribute("itemtype", "https://schema.org/Article")?;
+            self.write_attributes("article")?;
+            self.write_str(">")?;
+        }
+        Ok(())
+    }
+
+    /// Called after the last event of a document
+    fn end_document(&mut self) -> Result<(), HtmlError> {
+        let links = std::mem::take(&mut self.get_state().reference_links);
+        if !links.is_empty() {
+            self.write_reference_index(&links)?;
+        }
+        if self.get_config().html.schema_org {
+            self.write_str("</article>")?;
+        }
+        Ok(())
+    }
+
+    /// Write the list of reference link destinations ending a document
+    fn write_reference_index(&mut self, links: &[(String, String)]) -> Result<(), HtmlError> {
+        self.write_str("<ol")?;
+        self.write_attribute("class", "reference-index")?;
+        self.write_str(">")?;
+        for (href, title) in links {
+            self.write_str("<li><a")?;
+            self.write_href_attribute("href", href)?;
+            if !title.is_empty() {
+                let title = self.format_title(title);
+                self.write_attribute("title", &title)?;
+            }
+            self.write_str(">")?;
+            escape_html_body_text(self.get_writer(), href)
+                .map_err(|_| HtmlError::Write(std::fmt::Error))?;
+            self.write_str("</a></li>")?;
+        }
+        self.write_str("</ol>")
+    }
+
     fn start_paragraph(&mut self) -> Result<(), HtmlError> {
+        self.get_state().currently_in_paragraph = true;
+
+        let mut classes = Vec::new();
+        if self.get_state().block_contexts.is_empty() && !self.get_state().seen_first_paragraph {
+            self.get_state().seen_first_paragraph = true;
+            if let Some(lead_class) = &self.get_config().elements.paragraphs.lead_class {
+                classes.push(lead_class.clone());
+            }
+        }
+        if self.get_config().elements.paragraphs.preserve_newlines {
+            classes.push("preserve-newlines".to_string());
+        }
+        if std::mem::take(&mut self.get_state().paragraph_image_only) {
+            if let Some(class) = &self.get_config().elements.paragraphs.image_only_class {
+                classes.push(class.clone());
+            }
+        }
+
         if !self.get_state().currently_in_footnote {
             self.write_str("<p")?;
-            self.write_attributes("p")?;
+            self.write_generated_id("p")?;
+            self.write_attributes_with_classes("p", classes.iter().map(String::as_str))?;
             self.write_str(">")?;
         }
         Ok(())
     }
 
     fn end_paragraph(&mut self) -> Result<(), HtmlError> {
+        self.get_state().currently_in_paragraph = false;
         if !self.get_state().currently_in_footnote {
             self.write_str("</p>")?;
         }
@@ -70,8 +273,8 @@ pub trait HtmlWriter<W: StrWrite> {
     ) -> Result<(), HtmlError> {
         // Get all config values up front
         let level_num = level as u8;
-        let add_ids = self.get_config().elements.headings.add_ids;
-        let id_prefix = self.get_config().elements.headings.id_prefix.clone();
+        let text = std::mem::take(&mut self.get_state().heading_text);
+        let heading_id = self.assign_heading_id(level, &text, id)?;
         let level_classes = self
             .get_config()
             .elements
@@ -84,28 +287,46 @@ pub trait HtmlWriter<W: StrWrite> {
         self.write_str(&format!("<h{}", level_num))?;
 
         // Handle ID attribute
-        if add_ids {
-            let heading_id =
-                id.map_or_else(|| format!("{}{}", id_prefix, level_num), |s| s.to_string());
-            self.write_str(" id=\"")?;
-            escape_html(self.get_writer(), &heading_id)
-                .map_err(|_| HtmlError::Write(std::fmt::Error))?;
-            self.write_str("\"")?;
+        if let Some(heading_id) = heading_id {
+            self.write_attribute("id", &heading_id)?;
             self.get_state().heading_stack.push(heading_id);
         }
 
+        // Mark the first top-level heading as the article headline
+        if self.get_config().html.schema_org
+            && level == HeadingLevel::H1
+            && !self.get_state().headline_written
+        {
+            self.get_state().headline_written = true;
+            self.write_attribute("itemprop", "headline")?;
+        }
+
+        if self.get_config().elements.headings.emit_level_attr {
+            self.write_attribute("data-level", &level_num.to_string())?;
+        }
+
+        // Configured element attributes, with their classes merged below
+        let tag = format!("h{}", level_num);
+        let (element_classes, element_attrs): (Vec<_>, Vec<_>) = self
+            .element_attributes(&ElementInfo {
+                tag: &tag,
+                heading_level: Some(level_num),
+                ..Default::default()
+            })
+            .into_iter()
+            .partition(|(key, _)| key == "class");
+
         // Combine and handle classes
         let mut all_classes = Vec::new();
-        if let Some(level_class) = level_classes {
-            all_classes.push(level_class);
+        if let Some(level_class) = &level_classes {
+            all_classes.push(level_class.as_str());
         }
-        all_classes.extend(classes.iter().map(|s| s.to_string()));
+        all_classes.extend(classes.iter().map(|s| s.as_ref()));
+        all_classes.extend(element_classes.iter().map(|(_, value)| value.as_str()));
 
-        if !all_classes.is_empty() {
-            self.write_str(" class=\"")?;
-            escape_html(self.get_writer(), &all_classes.join(" "))
-                .map_err(|_| HtmlError::Write(std::fmt::Error))?;
-            self.write_str("\"")?;
+        let class = join_classes(all_classes, self.get_config().attributes.dedupe_classes);
+        if !class.is_empty() {
+            self.write_attribute("class", &class)?;
         }
 
         // Handle additional attributes
@@ -113,73 +334,277 @@ pub trait HtmlWriter<W: StrWrite> {
             self.write_str(" ")?;
             escape_html(self.get_writer(), key).map_err(|_| HtmlError::Write(std::fmt::Error))?;
             if let Some(val) = value {
-                self.write_str("=\"")?;
-                escape_html(self.get_writer(), val)
-                    .map_err(|_| HtmlError::Write(std::fmt::Error))?;
-                self.write_str("\"")?;
+                self.write_str("=")?;
+                self.write_quoted(val)?;
             }
         }
 
-        // Add any configured element attributes
-        self.write_attributes(&format!("h{}", level_num))?;
+        // Add any other configured element attributes
+        for (key, value) in element_attrs {
+            self.write_attribute(&key, &value)?;
+        }
 
         // Close the opening tag
-        self.write_str(">")
+        self.write_str(">")?;
+
+        if self.get_config().elements.headings.auto_number {
+            let number = self.section_number(level_num);
+            self.write_str(&number)?;
+            self.write_str(" ")?;
+        }
+        Ok(())
+    }
+
+    /// Advance the section counters for a heading and format its number, like `1.2.`
+    ///
+    /// Counters of deeper levels restart, and levels above the first heading
+    /// of the document are left out.
+    fn section_number(&mut self, level: u8) -> String {
+        let counters = &mut self.get_state().heading_counters;
+        counters.resize(level as usize, 0);
+        counters[level as usize - 1] += 1;
+        counters
+            .iter()
+            .skip_while(|&&count| count == 0)
+            .map(|count| format!("{}.", count))
+            .collect()
     }
+
+    /// Determine the ID of a heading and record it as used
+    fn assign_heading_id(
+        &mut self,
+        level: HeadingLevel,
+        text: &str,
+        id: Option<&str>,
+    ) -> Result<Option<String>, HtmlError> {
+        let mut heading_ids = std::mem::take(&mut self.get_state().heading_ids);
+        let heading_id =
+            self.get_config()
+                .elements
+                .headings
+                .heading_id(level as u8, text, id, &mut heading_ids);
+        self.get_state().heading_ids = heading_ids;
+        let heading_id = heading_id?;
+        if let Some(headings) = &mut self.get_state().collected_headings {
+            headings.push(HeadingInfo {
+                level,
+                text: text.to_string(),
+                id: heading_id.clone(),
+            });
+        }
+        Ok(heading_id)
+    }
+
     fn end_heading(&mut self, level: HeadingLevel) -> Result<(), HtmlError> {
         self.write_str(&format!("</{}>", level))
     }
 
     fn start_blockquote(&mut self) -> Result<(), HtmlError> {
-        self.write_str("<blockquote")?;
-        self.write_attributes("blockquote")?;
+        let tag = self.get_config().elements.blockquotes.tag.as_str();
+        self.write_str("<")?;
+        self.write_str(tag)?;
+        self.write_attributes(tag)?;
         self.write_str(">")?;
         Ok(())
     }
 
     fn end_blockquote(&mut self) -> Result<(), HtmlError> {
-        self.write_str("</blockquote>")
+        let tag = self.get_config().elements.blockquotes.tag.as_str();
+        self.write_str("</")?;
+        self.write_str(tag)?;
+        self.write_str(">")
+    }
+
+    /// Map a code block language through the configured aliases
+    fn resolve_language(&self, lang: &str) -> String {
+        self.get_config()
+            .elements
+            .code_blocks
+            .language_aliases
+            .get(lang)
+            .cloned()
+            .unwrap_or_else(|| lang.to_string())
     }
 
     fn start_code_block(&mut self, kind: CodeBlockKind) -> Result<(), HtmlError> {
         self.get_state().currently_in_code_block = true;
+        let info = match kind {
+            CodeBlockKind::Fenced(info) => parse_fence_info(&info),
+            CodeBlockKind::Indented => FenceInfo::default(),
+        };
+        let lang = match &info.language {
+            Some(lang) => Some(self.resolve_language(lang)),
+            None => self
+                .get_config()
+                .elements
+                .code_blocks
+                .default_language
+                .clone(),
+        };
+
+        self.start_code_block_wrapper()?;
+        self.write_code_block_open(lang.as_deref(), &info)?;
+        self.get_state().code_block_language = lang;
+        Ok(())
+    }
+
+    /// Open the `<pre>` of a code block, and its inner `<code>` unless the
+    /// language is configured to render as a bare `<pre>`
+    fn write_code_block_open(
+        &mut self,
+        lang: Option<&str>,
+        info: &FenceInfo,
+    ) -> Result<(), HtmlError> {
+        let bare = lang.is_some_and(|lang| {
+            self.get_config()
+                .elements
+                .code_blocks
+                .bare_pre_languages
+                .iter()
+                .any(|bare| bare == lang)
+        });
+        self.get_state().code_block_bare = bare;
+        self.get_state().code_block_start_line = info
+            .get("startline")
+            .and_then(|line| line.parse().ok())
+            .unwrap_or(1);
+
+        if self.get_config().elements.code_blocks.filename_header {
+            if let Some(filename) = info.get("title").or_else(|| info.get("filename")) {
+                self.write_str("<div")?;
+                self.write_attribute("class", "code-filename")?;
+                self.write_str(">")?;
+                escape_html_body_text(self.get_writer(), filename)
+                    .map_err(|_| HtmlError::Write(std::fmt::Error))?;
+                self.write_str("</div>")?;
+            }
+        }
+
         self.write_str("<pre")?;
+        if self.get_config().elements.code_blocks.info_attributes {
+            for (key, value) in &info.attributes {
+                self.write_attribute(&format!("data-{}", key), value)?;
+            }
+        }
         self.write_attributes("pre")?;
+        if bare {
+            return self.write_str(">");
+        }
+
         self.write_str("><code")?;
+        let class = lang.map(|lang| self.language_class(lang));
+        self.write_attributes_with_classes("code", class.as_deref())?;
+        if self
+            .get_config()
+            .elements
+            .code_blocks
+            .keep_original_language
+        {
+            if let Some(original) = info
+                .language
+                .as_deref()
+                .filter(|&original| Some(original) != lang)
+            {
+                self.write_attribute("data-lang-original", original)?;
+            }
+        }
+        let families = &self.get_config().elements.code_blocks.language_families;
+        if let Some(family) = lang.and_then(|lang| families.get(lang)).cloned() {
+            self.write_attribute("data-lang-family", &family)?;
+        }
+        self.write_str(">")
+    }
 
-        match kind {
-            CodeBlockKind::Fenced(info) => {
-                let lang = if info.is_empty() {
-                    self.get_config()
-                        .elements
-                        .code_blocks
-                        .default_language
-                        .as_deref()
-                } else {
-                    Some(&*info)
-                };
+    /// Close the elements opened by [`write_code_block_open`](Self::write_code_block_open)
+    fn write_code_block_close(&mut self) -> Result<(), HtmlError> {
+        if std::mem::take(&mut self.get_state().code_block_bare) {
+            self.write_str("</pre>")
+        } else {
+            self.write_str("</code></pre>")
+        }
+    }
 
-                if let Some(lang) = lang {
-                    self.write_str(&format!(" class=\"language-{}\"", lang))?;
-                }
-            }
-            CodeBlockKind::Indented => {
-                if let Some(lang) = &self.get_config().elements.code_blocks.default_language {
-                    self.write_str(&format!(" class=\"language-{}\"", lang))?;
-                }
+    /// Class attribute value for a code block in the given language
+    fn language_class(&self, lang: &str) -> String {
+        format!(
+            "{}{}",
+            self.get_config().elements.code_blocks.class_prefix,
+            lang
+        )
+    }
+
+    fn end_code_block(&mut self) -> Result<(), HtmlError> {
+        let code = self.take_code_text();
+        self.get_state().currently_in_code_block = false;
+
+        if self.get_config().elements.code_blocks.line_numbers {
+            let start = self.get_state().code_block_start_line;
+            for (index, line) in code.split_inclusive('\n').enumerate() {
+                self.write_line_number(start.saturating_add(index))?;
+                self.write_code_text(line)?;
             }
+        } else {
+            self.write_code_text(&code)?;
         }
+        self.write_code_block_close()?;
+        self.end_code_block_wrapper()
+    }
 
-        self.write_attributes("code")?;
+    /// Write the number shown at the start of a code block line
+    fn write_line_number(&mut self, number: usize) -> Result<(), HtmlError> {
+        self.write_str("<span")?;
+        self.write_attribute("class", "line-number")?;
         self.write_str(">")?;
+        self.write_str(&number.to_string())?;
+        self.write_str("</span>")
+    }
+
+    /// Take the buffered code block text, applying the configured transform hook
+    /// and blank line trimming
+    fn take_code_text(&mut self) -> String {
+        let code = std::mem::take(&mut self.get_state().code_buffer);
+        let lang = self.get_state().code_block_language.take();
+        let code = match &self.get_config().hooks.code_text_transform {
+            Some(transform) => transform(&code, lang.as_deref()),
+            None => code,
+        };
+        if self.get_config().elements.code_blocks.trim_blank_lines {
+            trim_blank_lines(&code).to_string()
+        } else {
+            code
+        }
+    }
+
+    /// Open the element wrapping a code block, if one is configured
+    fn start_code_block_wrapper(&mut self) -> Result<(), HtmlError> {
+        if self.get_config().elements.code_blocks.copy_button {
+            self.write_str("<div")?;
+            self.write_attributes_with_classes("div", ["code-block"])?;
+            self.write_str("><button")?;
+            self.write_attribute("class", "copy-code")?;
+            self.write_attribute("aria-label", "Copy")?;
+            self.write_str("></button>")?;
+        }
         Ok(())
     }
 
-    fn end_code_block(&mut self) -> Result<(), HtmlError> {
-        self.write_str("</code></pre>")
+    /// Close the element opened by [`start_code_block_wrapper`](Self::start_code_block_wrapper)
+    fn end_code_block_wrapper(&mut self) -> Result<(), HtmlError> {
+        if self.get_config().elements.code_blocks.copy_button {
+            self.write_str("</div>")?;
+        }
+        Ok(())
+    }
+
+    /// Write the buffered contents of a code block
+    fn write_code_text(&mut self, code: &str) -> Result<(), HtmlError> {
+        let html = &self.get_config().html;
+        let encode = html.encode_non_ascii && html.encode_non_ascii_in_code;
+        self.write_escaped(code, encode)
     }
 
     fn start_inline_code(&mut self) -> Result<(), HtmlError> {
+        self.get_state().currently_in_inline_code = true;
         self.write_str("<code")?;
         self.write_attributes("code")?;
         self.write_str(">")?;
@@ -187,10 +612,13 @@ pub trait HtmlWriter<W: StrWrite> {
     }
 
     fn end_inline_code(&mut self) -> Result<(), HtmlError> {
+        self.get_state().currently_in_inline_code = false;
         self.write_str("</code>")
     }
 
     fn start_list(&mut self, first_number: Option<u64>) -> Result<(), HtmlError> {
+        let contains_tasks = std::mem::take(&mut self.get_state().list_contains_tasks);
+        let task_list_class = contains_tasks.then_some("contains-task-list");
         match first_number {
             Some(n) => {
                 self.get_state().numbers.push(n.try_into().unwrap());
@@ -198,36 +626,95 @@ pub trait HtmlWriter<W: StrWrite> {
                     .list_stack
                     .push(ListContext::Ordered(n.try_into().unwrap()));
                 self.write_str("<ol")?;
-                if n != 1 {
-                    self.write_str(&format!(" start=\"{}\"", n))?;
+                if n != 1 || self.get_config().elements.lists.always_emit_start {
+                    self.write_attribute("start", &n.to_string())?;
                 }
-                self.write_attributes("ol")?;
+                self.write_list_depth()?;
+                self.write_attributes_with_classes("ol", task_list_class)?;
                 self.write_str(">")?;
             }
             None => {
                 self.get_state().list_stack.push(ListContext::Unordered);
                 self.write_str("<ul")?;
-                self.write_attributes("ul")?;
+                if let Some(marker) = self.get_config().elements.lists.unordered_marker.clone() {
+                    self.write_attribute("data-marker", &marker)?;
+                }
+                self.write_list_depth()?;
+                self.write_attributes_with_classes("ul", task_list_class)?;
                 self.write_str(">")?;
             }
         }
         Ok(())
     }
 
+    /// Write the nesting level of the list just started, if enabled
+    fn write_list_depth(&mut self) -> Result<(), HtmlError> {
+        if self.get_config().elements.lists.depth_attribute {
+            let depth = self.get_state().list_depth();
+            self.write_attribute("data-depth", &depth.to_string())?;
+        }
+        Ok(())
+    }
+
     fn end_list(&mut self, ordered: bool) -> Result<(), HtmlError> {
+        if let Some(ListContext::Ordered(_)) = self.get_state().list_stack.pop() {
+            self.get_state().numbers.pop();
+        }
         self.write_str(if ordered { "</ol>" } else { "</ul>" })
     }
 
     fn start_list_item(&mut self) -> Result<(), HtmlError> {
+        self.get_state().task_item_labels.push(false);
         self.write_str("<li")?;
-        self.write_attributes("li")?;
+        self.write_generated_id("li")?;
+
+        let mut classes = Vec::new();
+        if let Some(position) = self.get_state().list_item_positions.last_mut() {
+            let (index, count) = *position;
+            position.0 += 1;
+
+            if index == 0 {
+                classes.push("first");
+            }
+            if index + 1 == count {
+                classes.push("last");
+            }
+        }
+
+        if self.get_config().elements.lists.explicit_values {
+            if let Some(ListContext::Ordered(_)) = self.get_state().current_list_type() {
+                if let Some(number) = self.get_state().numbers.last_mut() {
+                    let value = *number;
+                    *number += 1;
+                    self.write_attribute("value", &value.to_string())?;
+                }
+            }
+        }
+
+        self.write_attributes_with_classes("li", classes)?;
         self.write_str(">")
     }
 
     fn end_list_item(&mut self) -> Result<(), HtmlError> {
+        if self.get_state().task_item_labels.pop() == Some(true) {
+            self.write_str("</label>")?;
+        }
         self.write_str("</li>")
     }
 
+    /// Close the label of the current task list item, if one is open
+    fn end_task_label(&mut self) -> Result<(), HtmlError> {
+        let open = self
+            .get_state()
+            .task_item_labels
+            .last_mut()
+            .is_some_and(std::mem::take);
+        if open {
+            self.write_str("</label>")?;
+        }
+        Ok(())
+    }
+
     fn start_table(&mut self, alignments: Vec<Alignment>) -> Result<(), HtmlError> {
         self.get_state().table_state = TableContext::InHeader;
         self.get_state().table_alignments = alignments;
@@ -242,7 +729,8 @@ pub trait HtmlWriter<W: StrWrite> {
 
     fn start_table_head(&mut self) -> Result<(), HtmlError> {
         self.get_state().table_cell_index = 0;
-        self.write_str("<thead><tr>")
+        self.write_str("<thead>")?;
+        self.write_table_row_open()
     }
 
     fn end_table_head(&mut self) -> Result<(), HtmlError> {
@@ -254,32 +742,73 @@ pub trait HtmlWriter<W: StrWrite> {
         if self.get_state().table_state == TableContext::InHeader {
             self.get_state().table_state = TableContext::InBody;
         }
-        self.write_str("<tr>")
+        self.write_table_row_open()
+    }
+
+    /// Write an opening `<tr>`, marking rows that lose cells to `max_columns`
+    fn write_table_row_open(&mut self) -> Result<(), HtmlError> {
+        self.write_str("<tr")?;
+        if let Some(max) = self.get_config().elements.tables.max_columns {
+            if self.get_state().table_alignments.len() > max {
+                self.write_attribute("data-truncated", "true")?;
+            }
+        }
+        self.write_str(">")
+    }
+
+    /// Whether the next table cell lies beyond the configured `max_columns`
+    fn is_cell_truncated(&mut self) -> bool {
+        let index = self.get_state().table_cell_index;
+        self.get_config()
+            .elements
+            .tables
+            .max_columns
+            .is_some_and(|max| index >= max)
     }
 
     fn end_table_row(&mut self) -> Result<(), HtmlError> {
         self.write_str("</tr>")
     }
 
-    fn start_table_cell(&mut self) -> Result<(), HtmlError> {
-        let tag = match self.get_state().table_state {
+    /// Tag name used for the table cell at the given index of the current row
+    fn table_cell_tag(&mut self, index: usize) -> &'static str {
+        let header_scopes = self.get_config().elements.tables.header_scopes;
+        match self.get_state().table_state {
             TableContext::InHeader => "th",
+            TableContext::InBody if header_scopes && index == 0 => "th",
             _ => "td",
-        };
+        }
+    }
+
+    fn start_table_cell(&mut self) -> Result<(), HtmlError> {
+        let idx = self.get_state().table_cell_index;
+        let tag = self.table_cell_tag(idx);
 
         self.write_str("<")?;
         self.write_str(tag)?;
-        let idx = self.get_state().table_cell_index;
+        if self.get_config().elements.tables.header_scopes {
+            match self.get_state().table_state {
+                TableContext::InHeader => self.write_attribute("scope", "col")?,
+                TableContext::InBody if idx == 0 => self.write_attribute("scope", "row")?,
+                _ => {}
+            }
+        }
         if let Some(alignment) = self.get_state().table_alignments.get(idx) {
             match alignment {
-                Alignment::Left => self.write_str(" style=\"text-align: left\"")?,
-                Alignment::Center => self.write_str(" style=\"text-align: center\"")?,
-                Alignment::Right => self.write_str(" style=\"text-align: right\"")?,
+                Alignment::Left => self.write_attribute("style", "text-align: left")?,
+                Alignment::Center => self.write_attribute("style", "text-align: center")?,
+                Alignment::Right => self.write_attribute("style", "text-align: right")?,
                 Alignment::None => {}
             }
         }
-
-        self.write_attributes(tag)?;
+        let column_class = self
+            .get_config()
+            .elements
+            .tables
+            .column_classes
+            .get(idx)
+            .cloned();
+        self.write_attributes_with_classes(tag, column_class.as_deref())?;
         self.write_str(">")?;
 
         self.get_state().table_cell_index += 1;
@@ -287,7 +816,12 @@ pub trait HtmlWriter<W: StrWrite> {
     }
 
     fn end_table_cell(&mut self) -> Result<(), HtmlError> {
-        self.write_str("</td>")
+        // The index was advanced when the cell was opened
+        let idx = self.get_state().table_cell_index.saturating_sub(1);
+        let tag = self.table_cell_tag(idx);
+        self.write_str("</")?;
+        self.write_str(tag)?;
+        self.write_str(">")
     }
 
     fn start_emphasis(&mut self) -> Result<(), HtmlError> {
@@ -311,47 +845,84 @@ pub trait HtmlWriter<W: StrWrite> {
     }
 
     fn start_strikethrough(&mut self) -> Result<(), HtmlError> {
-        self.write_str("<del")?;
-        self.write_attributes("del")?;
+        let tag = self.get_config().elements.strikethrough.tag.as_str();
+        self.write_str("<")?;
+        self.write_str(tag)?;
+        self.write_attributes(tag)?;
         self.write_str(">")
     }
 
     fn end_strikethrough(&mut self) -> Result<(), HtmlError> {
-        self.write_str("</del>")
+        let tag = self.get_config().elements.strikethrough.tag.as_str();
+        self.write_str("</")?;
+        self.write_str(tag)?;
+        self.write_str(">")
     }
 
     fn start_link(
         &mut self,
-        _link_type: LinkType,
+        link_type: LinkType,
         dest: &str,
         title: &str,
     ) -> Result<(), HtmlError> {
-        self.write_str("<a href=\"")?;
-        escape_href(self.get_writer(), dest).map_err(|_| HtmlError::Write(std::fmt::Error))?;
+        self.get_state().link_stack.push(link_type);
+        let is_reference = matches!(
+            link_type,
+            LinkType::Reference | LinkType::Collapsed | LinkType::Shortcut
+        );
+        if is_reference && self.get_config().elements.links.emit_reference_index {
+            let links = &mut self.get_state().reference_links;
+            if !links.iter().any(|(href, _)| href == dest) {
+                links.push((dest.to_string(), title.to_string()));
+            }
+        }
+        self.write_str("<a")?;
+        self.write_href_attribute("href", dest)?;
 
         if !title.is_empty() {
-            self.write_str("\" title=\"")?;
-            escape_html(self.get_writer(), title).map_err(|_| HtmlError::Write(std::fmt::Error))?;
+            let title = self.format_title(title);
+            self.write_attribute("title", &title)?;
         }
 
-        if self.is_external_link(dest) {
-            if self.get_config().elements.links.nofollow_external {
-                self.write_str("\" rel=\"nofollow")?;
-            }
-            if self.get_config().elements.links.open_external_blank {
-                self.write_str("\" target=\"_blank")?;
-            }
+        let anchor_class = match dest.starts_with('#') {
+            true => self.get_config().elements.links.anchor_class.clone(),
+            false => None,
+        };
+
+        if self.get_config().elements.links.is_download(dest) {
+            self.write_str(" download")?;
         }
 
-        self.write_str("\"")?;
-        self.write_attributes("a")?;
+        self.write_external_link_attributes(dest)?;
+        self.write_element_attributes_with_classes(
+            &ElementInfo {
+                tag: "a",
+                href: Some(dest),
+                ..Default::default()
+            },
+            anchor_class.as_deref(),
+        )?;
         self.write_str(">")
     }
 
     fn end_link(&mut self) -> Result<(), HtmlError> {
+        self.get_state().link_stack.pop();
         self.write_str("</a>")
     }
 
+    /// Write the rel/target attributes configured for links to external resources
+    fn write_external_link_attributes(&mut self, dest: &str) -> Result<(), HtmlError> {
+        if self.is_external_link(dest) {
+            if self.get_config().elements.links.nofollow_external {
+                self.write_attribute("rel", "nofollow")?;
+            }
+            if self.get_config().elements.links.open_external_blank {
+                self.write_attribute("target", "_blank")?;
+            }
+        }
+        Ok(())
+    }
+
     fn start_image<'a, I>(
         &mut self,
         _link_type: LinkType,
@@ -362,27 +933,122 @@ pub trait HtmlWriter<W: StrWrite> {
     where
         I: Iterator<Item = Event<'a>>,
     {
-        self.write_str("<img src=\"")?;
-        escape_href(self.get_writer(), dest).map_err(|_| HtmlError::Write(std::fmt::Error))?;
-        self.write_str("\" alt=\"")?;
+        let resolved;
+        let dest = match &self.get_config().hooks.image_src_resolver {
+            Some(resolve) => {
+                resolved = resolve(dest);
+                resolved.as_str()
+            }
+            None => dest,
+        };
+
+        let link_to_source = self.get_config().elements.images.link_to_source;
+        if link_to_source {
+            self.write_str("<a")?;
+            self.write_href_attribute("href", dest)?;
+            self.write_external_link_attributes(dest)?;
+            self.write_str(">")?;
+        }
+
+        let amp = self.get_config().html.amp_mode;
+        self.write_str(if amp { "<amp-img" } else { "<img" })?;
+        self.write_href_attribute("src", dest)?;
 
         let alt_text = self.collect_alt_text(iter);
-        escape_html(self.get_writer(), &alt_text).map_err(|_| HtmlError::Write(std::fmt::Error))?;
-        self.write_str("\"")?;
+        self.write_attribute("alt", &alt_text)?;
+
+        // With default dimensions configured, they come from a trailing `=WxH`
+        // in the title, then the configured attributes
+        let images = &self.get_config().elements.images;
+        let (title, size) = if images.default_width.is_some() || images.default_height.is_some() {
+            split_title_dimensions(title)
+        } else {
+            (title, None)
+        };
+        let mut attributes = self.element_attributes(&ElementInfo {
+            tag: "img",
+            href: Some(dest),
+            ..Default::default()
+        });
+        if amp {
+            attributes.retain(|(key, _)| !key.starts_with("on") && key != "style");
+        }
+        let first = !std::mem::replace(&mut self.get_state().image_seen, true);
+        if let Some(class) = self.get_config().elements.images.first_image_class.clone() {
+            if first {
+                // Merge the class with any configured ones into a single attribute
+                let (classes, others): (Vec<_>, Vec<_>) =
+                    attributes.into_iter().partition(|(key, _)| key == "class");
+                let classes = std::iter::once(class.as_str())
+                    .chain(classes.iter().map(|(_, value)| value.as_str()));
+                let class = join_classes(classes, self.get_config().attributes.dedupe_classes);
+                self.write_attribute("class", &class)?;
+                attributes = others;
+            }
+        }
+        let images = &self.get_config().elements.images;
+        let (width, height) = match size {
+            Some((width, height)) => (Some(width), Some(height)),
+            None => (images.default_width, images.default_height),
+        };
+        for (name, value) in [("width", width), ("height", height)] {
+            let configured = attributes.iter().any(|(key, _)| key == name);
+            if let Some(value) = value.filter(|_| !configured) {
+                self.write_attribute(name, &value.to_string())?;
+            } else if amp && !configured {
+                return Err(HtmlError::Render(format!(
+                    "image '{}' has no {}, which AMP requires",
+                    dest, name
+                )));
+            }
+        }
 
         if !title.is_empty() {
-            self.write_str(" title=\"")?;
-            escape_html(self.get_writer(), title).map_err(|_| HtmlError::Write(std::fmt::Error))?;
-            self.write_str("\"")?;
+            let title = self.format_title(title);
+            self.write_attribute("title", &title)?;
         }
 
-        self.write_attributes("img")?;
+        let srcset = self
+            .get_config()
+            .hooks
+            .srcset_resolver
+            .as_ref()
+            .and_then(|resolve| resolve(dest));
+        if let Some(handler) = self
+            .get_config()
+            .elements
+            .images
+            .fallback_src
+            .as_deref()
+            .filter(|_| !amp)
+            .and_then(fallback_onerror)
+        {
+            self.write_attribute("onerror", &handler)?;
+        }
 
-        if self.get_config().html.xhtml_style {
+        if let Some(srcset) = srcset {
+            self.write_attribute("srcset", &srcset)?;
+            if let Some(sizes) = self.get_config().elements.images.default_sizes.clone() {
+                self.write_attribute("sizes", &sizes)?;
+            }
+        }
+
+        for (key, value) in attributes {
+            self.write_attribute(&key, &value)?;
+        }
+
+        if amp {
+            self.write_str("></amp-img>")?;
+        } else if self.get_config().html.xhtml_style {
             self.write_str(" />")?;
         } else {
             self.write_str(">")?;
         }
+
+        // The alt text collection consumed the image's end tag, so close the wrapper here
+        if link_to_source {
+            self.write_str("</a>")?;
+        }
         Ok(())
     }
 
@@ -391,17 +1057,22 @@ pub trait HtmlWriter<W: StrWrite> {
     }
 
     fn footnote_reference(&mut self, name: &str) -> Result<(), HtmlError> {
-        self.write_str("<sup class=\"footnote-reference\"><a href=\"#")?;
-        self.write_str(name)?;
-        self.write_str("\">")?;
+        self.write_str("<sup")?;
+        self.write_attribute("class", "footnote-reference")?;
+        self.write_str("><a")?;
+        self.write_href_attribute("href", &format!("#{}", name))?;
+        self.write_str(">")?;
         self.write_str(name)?;
         self.write_str("</a></sup>")
     }
 
     fn start_footnote_definition(&mut self, name: &str) -> Result<(), HtmlError> {
-        self.write_str("<div class=\"footnote-definition\" id=\"")?;
-        self.write_str(name)?;
-        self.write_str("\"><sup class=\"footnote-definition-label\">")?;
+        self.write_str("<div")?;
+        self.write_attribute("class", "footnote-definition")?;
+        self.write_attribute("id", name)?;
+        self.write_str("><sup")?;
+        self.write_attribute("class", "footnote-definition-label")?;
+        self.write_str(">")?;
         self.write_str(name)?;
         self.get_state().currently_in_footnote = true;
         self.write_str("</sup>")?;
@@ -416,7 +1087,27 @@ pub trait HtmlWriter<W: StrWrite> {
 
     // Task list handlers
     fn task_list_item(&mut self, checked: bool) -> Result<(), HtmlError> {
-        self.write_str("<input type=\"checkbox\" disabled")?;
+        if self.get_config().elements.task_lists.wrap_label {
+            if let Some(label) = self.get_state().task_item_labels.last_mut() {
+                *label = true;
+                self.write_str("<label>")?;
+            }
+        }
+        self.checkbox(checked)
+    }
+
+    /// Write a disabled checkbox, as used for task list items
+    fn checkbox(&mut self, checked: bool) -> Result<(), HtmlError> {
+        if self.get_config().html.commonmark_strict {
+            return if checked {
+                self.write_str("<input disabled=\"\" type=\"checkbox\" checked=\"\"/>\n")
+            } else {
+                self.write_str("<input disabled=\"\" type=\"checkbox\"/>\n")
+            };
+        }
+        self.write_str("<input")?;
+        self.write_attribute("type", "checkbox")?;
+        self.write_str(" disabled")?;
         if checked {
             self.write_str(" checked")?;
         }
@@ -425,34 +1116,275 @@ pub trait HtmlWriter<W: StrWrite> {
 
     // Special elements - simple HTML
     fn horizontal_rule(&mut self) -> Result<(), HtmlError> {
-        self.write_str("<hr>")
+        if let HrRender::Div(class) = &self.get_config().elements.hr.render_as {
+            let class = class.clone();
+            return self.section_divider(&class);
+        }
+
+        self.write_str("<hr")?;
+        self.write_section_number()?;
+        self.write_attributes("hr")?;
+        if self.get_config().html.xhtml_style {
+            self.write_str(" />")
+        } else {
+            self.write_str(">")
+        }
+    }
+
+    /// Write a thematic break as a `<div>` with the given class
+    fn section_divider(&mut self, class: &str) -> Result<(), HtmlError> {
+        self.write_str("<div")?;
+        self.write_section_number()?;
+        self.write_attributes_with_classes("div", [class])?;
+        self.write_str("></div>")
+    }
+
+    /// Write the `data-section` number of a thematic break, if configured
+    fn write_section_number(&mut self) -> Result<(), HtmlError> {
+        if self.get_config().elements.hr.number_sections {
+            self.get_state().rule_count += 1;
+            let number = self.get_state().rule_count;
+            self.write_attribute("data-section", &number.to_string())?;
+        }
+        Ok(())
     }
 
     fn soft_break(&mut self) -> Result<(), HtmlError> {
-        if self.get_config().html.break_on_newline {
-            self.write_str("<br>")
+        let preserve = self.get_config().elements.paragraphs.preserve_newlines
+            && self.get_state().currently_in_paragraph;
+        if self.get_config().html.break_on_newline && !preserve {
+            self.write_line_break()
         } else {
-            self.write_str("\n")
+            self.newline()
         }
     }
 
+    /// Write a newline using the configured line ending
+    fn newline(&mut self) -> Result<(), HtmlError> {
+        let line_ending = self.get_config().html.line_ending;
+        self.write_str(line_ending.as_str())
+    }
+
     fn hard_break(&mut self) -> Result<(), HtmlError> {
-        self.write_str("<br>")
+        self.write_line_break()
+    }
+
+    /// Write a `<br>`, followed by a newline if configured
+    fn write_line_break(&mut self) -> Result<(), HtmlError> {
+        if self.get_config().html.xhtml_style {
+            self.write_str("<br />")?;
+        } else {
+            self.write_str("<br>")?;
+        }
+        if self.get_config().html.break_with_newline {
+            self.newline()?;
+        }
+        Ok(())
     }
 
     fn text(&mut self, text: &str) -> Result<(), HtmlError> {
-        if self.get_config().html.escape_html {
+        if self.get_state().currently_in_code_block {
+            self.get_state().code_buffer.push_str(text);
+            return Ok(());
+        }
+
+        if self.get_config().html.pandoc_sup_sub && !self.get_state().currently_in_inline_code {
+            let mut last = 0;
+            for (delim, range) in find_sup_sub(text) {
+                self.write_inline_text(&text[last..range.start])?;
+                let inner = &text[range.start + 1..range.end - 1];
+                if delim == '^' {
+                    self.superscript(inner)?;
+                } else {
+                    self.subscript(inner)?;
+                }
+                last = range.end;
+            }
+            return self.write_inline_text(&text[last..]);
+        }
+
+        self.write_inline_text(text)
+    }
+
+    /// Write text outside code blocks, autolinking bare URLs if configured
+    fn write_inline_text(&mut self, text: &str) -> Result<(), HtmlError> {
+        let autolink = self.get_config().html.autolink_bare_urls
+            && !self.get_state().currently_in_inline_code
+            && self.get_state().link_stack.is_empty();
+        if autolink {
+            return self.write_autolinked_text(text);
+        }
+
+        self.write_prose_text(text)
+    }
+
+    /// Write text outside code, replacing emoji shortcodes and wrapping dates if configured
+    fn write_prose_text(&mut self, text: &str) -> Result<(), HtmlError> {
+        if self.get_state().currently_in_inline_code {
+            return self.write_code_span_text(text);
+        }
+        if self.get_config().html.emoji == EmojiMode::Off {
+            return self.write_dated_text(text);
+        }
+
+        let mut last = 0;
+        for (range, emoji) in find_emoji_shortcodes(text) {
+            self.write_dated_text(&text[last..range.start])?;
+            self.write_emoji(&text[range.clone()], emoji)?;
+            last = range.end;
+        }
+        self.write_dated_text(&text[last..])
+    }
+
+    /// Write an emoji for a shortcode, according to the configured emoji mode
+    fn write_emoji(&mut self, shortcode: &str, emoji: &str) -> Result<(), HtmlError> {
+        let EmojiMode::Image(template) = &self.get_config().html.emoji else {
+            return self.write_str(emoji);
+        };
+        let src = template.replace("{name}", shortcode.trim_matches(':'));
+        self.write_str("<img")?;
+        self.write_href_attribute("src", &src)?;
+        self.write_attribute("alt", shortcode)?;
+        self.write_attributes_with_classes("img", ["emoji"])?;
+        if self.get_config().html.xhtml_style {
+            self.write_str(" />")
+        } else {
+            self.write_str(">")
+        }
+    }
+
+    /// Write text, wrapping ISO dates in `<time>` if configured
+    fn write_dated_text(&mut self, text: &str) -> Result<(), HtmlError> {
+        if !self.get_config().html.detect_dates {
+            return self.write_text(text);
+        }
+
+        let mut last = 0;
+        for range in find_iso_dates(text) {
+            self.write_text(&text[last..range.start])?;
+            let date = &text[range.clone()];
+            self.write_str("<time")?;
+            self.write_attribute("datetime", date)?;
+            self.write_str(">")?;
+            self.write_str(date)?;
+            self.write_str("</time>")?;
+            last = range.end;
+        }
+        self.write_text(&text[last..])
+    }
+
+    fn superscript(&mut self, text: &str) -> Result<(), HtmlError> {
+        self.write_str("<sup")?;
+        self.write_attributes("sup")?;
+        self.write_str(">")?;
+        self.write_text(text)?;
+        self.write_str("</sup>")
+    }
+
+    fn subscript(&mut self, text: &str) -> Result<(), HtmlError> {
+        self.write_str("<sub")?;
+        self.write_attributes("sub")?;
+        self.write_str(">")?;
+        self.write_text(text)?;
+        self.write_str("</sub>")
+    }
+
+    /// Write text content, escaping it if configured
+    fn write_text(&mut self, text: &str) -> Result<(), HtmlError> {
+        let encode = self.get_config().html.encode_non_ascii;
+        self.write_escaped(text, encode)
+    }
+
+    /// Write text, escaped if configured, and with non-ASCII characters as
+    /// character references if `encode` is set
+    ///
+    /// Text is always escaped in `commonmark_strict` mode, like the reference
+    /// renderer does, while raw HTML is still passed through.
+    fn write_escaped(&mut self, text: &str, encode: bool) -> Result<(), HtmlError> {
+        let html = &self.get_config().html;
+        let escape = html.escape_html || html.commonmark_strict;
+        if encode {
+            let mut escaped = String::new();
+            if escape {
+                escape_html_body_text(&mut escaped, text)
+                    .map_err(|_| HtmlError::Write(std::fmt::Error))?;
+            } else {
+                escaped.push_str(text);
+            }
+            let mut encoded = String::new();
+            encode_non_ascii(&mut encoded, &escaped);
+            self.write_str(&encoded)
+        } else if escape {
             escape_html_body_text(self.get_writer(), text)
-                .map_err(|_| HtmlError::Write(std::fmt::Error))?;
+                .map_err(|_| HtmlError::Write(std::fmt::Error))
         } else {
-            self.write_str(text)?;
+            self.write_str(text)
         }
-        Ok(())
+    }
+
+    /// Write the text of an inline code span, adding `<wbr>` after separators if configured
+    fn write_code_span_text(&mut self, text: &str) -> Result<(), HtmlError> {
+        if !self.get_config().elements.code_spans.insert_wbr {
+            return self.write_text(text);
+        }
+
+        let mut last = 0;
+        for (index, c) in text.char_indices() {
+            let end = index + c.len_utf8();
+            let separator =
+                matches!(c, '.' | '/' | '-') || (c == ':' && text[..index].ends_with(':'));
+            if separator && end < text.len() {
+                self.write_text(&text[last..end])?;
+                self.write_str("<wbr>")?;
+                last = end;
+            }
+        }
+        self.write_text(&text[last..])
+    }
+
+    /// Write text content, wrapping any bare URLs in links
+    fn write_autolinked_text(&mut self, text: &str) -> Result<(), HtmlError> {
+        let mut last = 0;
+        for range in find_bare_urls(text) {
+            self.write_prose_text(&text[last..range.start])?;
+
+            let url = &text[range.clone()];
+            self.write_str("<a")?;
+            self.write_href_attribute("href", url)?;
+            self.write_external_link_attributes(url)?;
+            self.write_attributes("a")?;
+            self.write_str(">")?;
+            self.write_text(url)?;
+            self.write_str("</a>")?;
+
+            last = range.end;
+        }
+        self.write_prose_text(&text[last..])
+    }
+
+    /// Write an abbreviation with its expansion as a tooltip
+    fn abbreviation(&mut self, text: &str, title: &str) -> Result<(), HtmlError> {
+        self.write_str("<abbr")?;
+        self.write_attribute("title", title)?;
+        self.write_attributes("abbr")?;
+        self.write_str(">")?;
+        self.write_text(text)?;
+        self.write_str("</abbr>")
+    }
+
+    /// Write a keyboard input span
+    fn kbd(&mut self, keys: &str) -> Result<(), HtmlError> {
+        self.write_str("<kbd")?;
+        self.write_attributes("kbd")?;
+        self.write_str(">")?;
+        self.write_text(keys)?;
+        self.write_str("</kbd>")
     }
 
     fn start_definition_list(&mut self) -> Result<(), HtmlError> {
         self.write_str("<dl")?;
-        self.write_attributes("dl")?;
+        let class = self.get_config().elements.definition_lists.class.clone();
+        self.write_attributes_with_classes("dl", class.as_deref())?;
         self.write_str(">")
     }
 
@@ -462,6 +1394,14 @@ pub trait HtmlWriter<W: StrWrite> {
 
     fn start_definition_list_title(&mut self) -> Result<(), HtmlError> {
         self.write_str("<dt")?;
+        let text = std::mem::take(&mut self.get_state().term_text);
+        if self.get_config().elements.definition_lists.term_ids {
+            let slug = sanitize_id(&text);
+            if !slug.is_empty() {
+                let id = self.get_state().heading_ids.assign(slug, true);
+                self.write_attribute("id", &id)?;
+            }
+        }
         self.write_attributes("dt")?;
         self.write_str(">")
     }
@@ -495,7 +1435,76 @@ pub trait HtmlWriter<W: StrWrite> {
     }
 
     fn html_raw(&mut self, html: &CowStr) -> Result<(), HtmlError> {
-        self.write_str(html)
+        self.write_raw_html(html)
+    }
+
+    /// Write the content of a raw HTML block, through the configured filter hook
+    fn html_block(&mut self, html: &str) -> Result<(), HtmlError> {
+        match self.get_config().hooks.raw_html_passthrough_filter.clone() {
+            Some(filter) => self.write_raw_html(&filter(html)),
+            None => self.write_raw_html(html),
+        }
+    }
+
+    /// Write raw HTML from the input, escaping it if configured
+    fn write_raw_html(&mut self, html: &str) -> Result<(), HtmlError> {
+        if self.get_config().html.escape_html {
+            let mut escaped = String::with_capacity(html.len());
+            escape_html_keep_entities(&mut escaped, html);
+            self.write_str(&escaped)
+        } else {
+            self.write_str(html)
+        }
+    }
+
+    fn inline_math(&mut self, tex: &str) -> Result<(), HtmlError> {
+        self.write_math(tex, false)
+    }
+
+    fn display_math(&mut self, tex: &str) -> Result<(), HtmlError> {
+        self.write_math(tex, true)
+    }
+
+    /// Write math through the configured renderer hook, or as a span holding the TeX source
+    fn write_math(&mut self, tex: &str, display: bool) -> Result<(), HtmlError> {
+        if let Some(renderer) = self.get_config().hooks.math_renderer.clone() {
+            return self.write_str(&renderer(tex, display));
+        }
+
+        let class = if display {
+            "math math-display"
+        } else {
+            "math math-inline"
+        };
+        self.write_str("<span")?;
+        self.write_attributes_with_classes("span", [class])?;
+        self.write_str(">")?;
+        self.write_text(tex)?;
+        self.write_str("</span>")
+    }
+
+    /// Handle an event that has no HTML mapping, according to the configured policy
+    fn unknown_event(&mut self, source: &str) -> Result<(), HtmlError> {
+        match self.get_config().html.unknown_event_policy {
+            UnknownPolicy::Ignore => Ok(()),
+            UnknownPolicy::Comment => {
+                // A comment must not contain "--", so split up any runs of dashes
+                let mut comment = String::with_capacity(source.len());
+                for c in source.chars() {
+                    if c == '-' && comment.ends_with('-') {
+                        comment.push(' ');
+                    }
+                    comment.push(c);
+                }
+                self.write_str("<!-- ")?;
+                self.write_str(&comment)?;
+                self.write_str(" -->")
+            }
+            UnknownPolicy::Error => Err(HtmlError::Render(format!(
+                "no HTML mapping for event: {}",
+                source
+            ))),
+        }
     }
 
     fn collect_alt_text<'a, I>(&self, iter: &mut Peekable<I>) -> String
@@ -530,6 +1539,53 @@ pub trait HtmlWriter<W: StrWrite> {
     }
 }
 
+/// Build an `onerror` handler swapping in a fallback image, if the URL is safe to use
+fn fallback_onerror(src: &str) -> Option<String> {
+    let scheme = src
+        .split_once(':')
+        .map(|(scheme, _)| scheme)
+        .filter(|scheme| !scheme.contains(['/', '?', '#']));
+    if let Some(scheme) = scheme {
+        if !scheme.eq_ignore_ascii_case("http") && !scheme.eq_ignore_ascii_case("https") {
+            return None;
+        }
+    }
+
+    // Percent-encoding quotes and backslashes keeps the URL inside the script string
+    let mut url = String::new();
+    crate::utils::escape_href(&mut url, &src.replace('\\', "%5C"));
+    Some(format!("this.onerror=null;this.src='{}'", url))
+}
+
+/// Add attributes to a list, replacing the value of any key already present.
+/// Classes accumulate instead, and are merged when the element is written.
+fn merge_attributes(
+    attrs: &mut Vec<(String, String)>,
+    more: impl IntoIterator<Item = (String, String)>,
+) {
+    for (key, value) in more {
+        match attrs.iter_mut().find(|(k, _)| k == &key && k != "class") {
+            Some((_, existing)) => *existing = value,
+            None => attrs.push((key, value)),
+        }
+    }
+}
+
+/// Escape an attribute value for use inside single quotes
+fn escape_single_quoted(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '\'' => escaped.push_str("&#x27;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 // Default bases to derive from, implements the default getter methods.
 pub struct HtmlWriterBase<W: StrWrite> {
     writer: W,
@@ -720,6 +1776,97 @@ mod tests {
         assert_eq!(output, "</ul>");
     }
 
+    #[test]
+    fn test_single_quoted_attributes() {
+        let mut output = String::new();
+        let mut handler = TestHandler::new(FmtWriter(&mut output));
+        handler.config.html.attribute_quote = AttrQuote::Single;
+        handler
+            .start_link(LinkType::Inline, "/page", "It's \"quoted\"")
+            .unwrap();
+        handler.text("Link").unwrap();
+        handler.end_link().unwrap();
+        assert_eq!(
+            output,
+            "<a href='/page' title='It&#x27;s \"quoted\"'>Link</a>"
+        );
+    }
+
+    #[test]
+    fn test_unknown_event_policies() {
+        let mut output = String::new();
+        let mut handler = TestHandler::new(FmtWriter(&mut output));
+        handler.unknown_event("$x$").unwrap();
+        assert_eq!(output, "");
+
+        let mut output = String::new();
+        let mut handler = TestHandler::new(FmtWriter(&mut output));
+        handler.config.html.unknown_event_policy = UnknownPolicy::Comment;
+        handler.unknown_event("a -- b").unwrap();
+        assert_eq!(output, "<!-- a - - b -->");
+
+        let mut output = String::new();
+        let mut handler = TestHandler::new(FmtWriter(&mut output));
+        handler.config.html.unknown_event_policy = UnknownPolicy::Error;
+        assert!(matches!(
+            handler.unknown_event("$x$"),
+            Err(HtmlError::Render(_))
+        ));
+    }
+
+    #[test]
+    fn test_abbreviation() {
+        let mut output = String::new();
+        let mut handler = TestHandler::new(FmtWriter(&mut output));
+        handler.config.html.escape_html = true;
+        handler
+            .abbreviation("R&D", "Research & \"Development\"")
+            .unwrap();
+        assert_eq!(
+            output,
+            "<abbr title=\"Research &amp; &quot;Development&quot;\">R&amp;D</abbr>"
+        );
+    }
+
+    #[test]
+    fn test_math_default() {
+        let mut output = String::new();
+        let mut handler = TestHandler::new(FmtWriter(&mut output));
+        handler.config.html.escape_html = true;
+        handler.inline_math("a<b").unwrap();
+        handler.display_math("x^2").unwrap();
+        assert_eq!(
+            output,
+            "<span class=\"math math-inline\">a&lt;b</span>\
+             <span class=\"math math-display\">x^2</span>"
+        );
+    }
+
+    #[test]
+    fn test_superscript_subscript() {
+        let mut output = String::new();
+        let mut handler = TestHandler::new(FmtWriter(&mut output));
+        handler.config.html.escape_html = true;
+        handler.superscript("2").unwrap();
+        handler.subscript("i<j").unwrap();
+        assert_eq!(output, "<sup>2</sup><sub>i&lt;j</sub>");
+    }
+
+    #[test]
+    fn test_kbd() {
+        let mut output = String::new();
+        let mut handler = TestHandler::new(FmtWriter(&mut output));
+        handler.config.html.escape_html = true;
+        handler.kbd("Ctrl+<").unwrap();
+        assert_eq!(output, "<kbd>Ctrl+&lt;</kbd>");
+
+        // Like other text, keys are only escaped if configured
+        let mut output = String::new();
+        let mut handler = TestHandler::new(FmtWriter(&mut output));
+        handler.kbd("Ctrl+&amp;").unwrap();
+        assert_eq!(output, "<kbd>Ctrl+&amp;</kbd>");
+    }
+
     #[test]
     fn test_table_structure() {
         let mut output = String::new();
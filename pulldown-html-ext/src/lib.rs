@@ -75,10 +75,19 @@
 mod html;
 pub mod utils;
 pub use html::{
-    create_html_renderer, push_html, push_html_with_highlighting, write_html_fmt, write_html_io,
-    AttributeMappings, CodeBlockOptions, DefaultHtmlWriter, ElementOptions, HeadingOptions,
-    HtmlConfig, HtmlError, HtmlOptions, HtmlRenderer, HtmlState, HtmlWriter, LinkOptions,
-    SyntectConfig, SyntectConfigStyle, SyntectWriter,
+    create_html_renderer, extract_headings, push_html, push_html_with_highlighting, render_bytes,
+    render_excerpt, render_section, render_with_frontmatter, render_with_highlighting,
+    render_with_stats, rendered_len, write_html_fmt, write_html_io, AttrQuote, AttributeMappings,
+    AttributeProvider, BlockContext, BlockquoteOptions, BlockquoteTag, CodeBlockOptions,
+    CodeSpanOptions, CodeTextTransform, CountingWriter, CssMode, DefaultHtmlWriter,
+    DefinitionListOptions, DupPolicy, ElementHook, ElementInfo, ElementOptions, EmojiMode,
+    EmptyHref, FootnoteOptions, FrontMatter, HeadingIds, HeadingInfo, HeadingOptions,
+    HeadingRenderer, HighlightedHtml, HrOptions, HrRender, HtmlConfig, HtmlError, HtmlOptions,
+    HtmlRenderer, HtmlState, HtmlWriter, IdGenerator, IdSource, ImageOptions, ImageSrcResolver,
+    LineEnding, LinkOptions, ListContext, ListOptions, MathRenderer, ParagraphOptions,
+    RawHtmlFilter, RenderHooks, RenderStats, SrcsetResolver, StrikeTag, StrikethroughOptions,
+    SyntectConfig, SyntectConfigStyle, SyntectWriter, TableContext, TableOptions, TaskListOptions,
+    UnknownPolicy,
 };
 pub use pulldown_html_ext_derive::html_writer;
 
@@ -103,6 +112,24 @@ mod tests_lib {
         assert!(output.contains("This is a test."));
     }
 
+    #[test]
+    fn test_footnote_options_exported() {
+        let mut config = HtmlConfig::default();
+        config.elements.footnotes = FootnoteOptions {
+            collect_at_end: true,
+        };
+
+        let markdown = "Text[^1]\n\n[^1]: Note\n\nAfter";
+        let mut options = pulldown_cmark::Options::empty();
+        options.insert(pulldown_cmark::Options::ENABLE_FOOTNOTES);
+        let parser = Parser::new_ext(markdown, options);
+        let mut output = String::new();
+
+        push_html(&mut output, parser, &config).unwrap();
+
+        assert!(output.find("After").unwrap() < output.find("Note").unwrap());
+    }
+
     #[test]
     fn test_custom_heading_classes() {
         let mut config = HtmlConfig::default();
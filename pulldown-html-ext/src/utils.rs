@@ -1,6 +1,7 @@
 //! Utility functions for HTML rendering and string manipulation
 
 use pulldown_cmark_escape::StrWrite;
+use std::ops::Range;
 /// Escape special HTML characters in a string
 ///
 /// # Arguments
@@ -29,6 +30,89 @@ pub fn escape_html(output: &mut String, text: &str) {
     }
 }
 
+/// Escape special HTML characters, leaving existing character references intact
+///
+/// Like [`escape_html`], except that an `&` starting a named or numeric
+/// character reference such as `&amp;` or `&#39;` is kept, so text that is
+/// already partly escaped is not escaped twice.
+///
+/// # Arguments
+///
+/// * `output` - The string buffer to write to
+/// * `text` - The text to escape
+///
+/// # Example
+///
+/// ```
+/// let mut output = String::new();
+/// pulldown_html_ext::utils::escape_html_keep_entities(&mut output, "<b>Tom &amp; Jerry & co</b>");
+/// assert_eq!(output, "&lt;b&gt;Tom &amp; Jerry &amp; co&lt;/b&gt;");
+/// ```
+pub fn escape_html_keep_entities(output: &mut String, text: &str) {
+    for (i, c) in text.char_indices() {
+        match c {
+            '&' if starts_with_entity(&text[i + 1..]) => output.push('&'),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '&' => output.push_str("&amp;"),
+            '\'' => output.push_str("&#x27;"),
+            _ => output.push(c),
+        }
+    }
+}
+
+/// Length of the longest character reference name, `CounterClockwiseContourIntegral`
+const MAX_ENTITY_LEN: usize = 31;
+
+/// Whether text following an `&` completes a character reference
+fn starts_with_entity(text: &str) -> bool {
+    // Only look as far as a reference could reach
+    let end = text
+        .bytes()
+        .take(MAX_ENTITY_LEN + 1)
+        .position(|b| !b.is_ascii_alphanumeric() && b != b'#');
+    let Some(end) = end.filter(|&end| text.as_bytes()[end] == b';') else {
+        return false;
+    };
+    let name = &text[..end];
+    if let Some(hex) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+        !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit())
+    } else if let Some(decimal) = name.strip_prefix('#') {
+        !decimal.is_empty() && decimal.chars().all(|c| c.is_ascii_digit())
+    } else {
+        name.starts_with(|c: char| c.is_ascii_alphabetic())
+            && name.chars().all(|c| c.is_ascii_alphanumeric())
+    }
+}
+
+/// Write text with every non-ASCII character as a numeric character reference
+///
+/// ASCII text, including any character references already in it, is copied
+/// unchanged, so escaped text is never escaped twice.
+///
+/// # Arguments
+///
+/// * `output` - The string buffer to write to
+/// * `text` - The text to encode
+///
+/// # Example
+///
+/// ```
+/// let mut output = String::new();
+/// pulldown_html_ext::utils::encode_non_ascii(&mut output, "café &amp; crème");
+/// assert_eq!(output, "caf&#233; &amp; cr&#232;me");
+/// ```
+pub fn encode_non_ascii(output: &mut String, text: &str) {
+    for c in text.chars() {
+        if c.is_ascii() {
+            output.push(c);
+        } else {
+            output.push_str(&format!("&#{};", c as u32));
+        }
+    }
+}
+
 /// Escape special characters in URLs
 ///
 /// # Arguments
@@ -85,6 +169,478 @@ pub fn sanitize_id(text: &str) -> String {
         .join("-")
 }
 
+/// Collapse runs of whitespace into single spaces
+///
+/// Leading and trailing whitespace is removed, and any internal run of
+/// whitespace (including newlines) becomes a single space.
+///
+/// # Arguments
+///
+/// * `text` - The text to normalize
+///
+/// # Example
+///
+/// ```
+/// let title = pulldown_html_ext::utils::normalize_whitespace("A title\n  over lines");
+/// assert_eq!(title, "A title over lines");
+/// ```
+pub fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Split image dimensions written as a trailing `=WxH` off a title
+///
+/// Returns the rest of the title, with surrounding whitespace removed, and the
+/// width and height if the title ends with dimensions.
+///
+/// # Arguments
+///
+/// * `title` - The image title
+///
+/// # Example
+///
+/// ```
+/// let (title, size) = pulldown_html_ext::utils::split_title_dimensions("Logo =640x480");
+/// assert_eq!(title, "Logo");
+/// assert_eq!(size, Some((640, 480)));
+/// ```
+pub fn split_title_dimensions(title: &str) -> (&str, Option<(u32, u32)>) {
+    let trimmed = title.trim_end();
+    let (rest, last) = match trimmed.rfind(char::is_whitespace) {
+        Some(index) => (&trimmed[..index], &trimmed[index..]),
+        None => ("", trimmed),
+    };
+    let size = last.trim_start().strip_prefix('=').and_then(|size| {
+        let (width, height) = size.split_once('x')?;
+        Some((width.parse().ok()?, height.parse().ok()?))
+    });
+    match size {
+        Some(size) => (rest.trim(), Some(size)),
+        None => (title, None),
+    }
+}
+
+/// Remove leading and trailing blank lines from a block of text
+///
+/// Lines consisting only of whitespace are stripped from the start and end
+/// of the text. Blank lines between other lines are preserved, as is the
+/// line ending of the last remaining line.
+///
+/// # Arguments
+///
+/// * `text` - The text to trim
+///
+/// # Example
+///
+/// ```
+/// let code = pulldown_html_ext::utils::trim_blank_lines("\n  \nfn main() {}\n\n");
+/// assert_eq!(code, "fn main() {}\n");
+/// ```
+pub fn trim_blank_lines(text: &str) -> &str {
+    let mut start = None;
+    let mut end = 0;
+    let mut offset = 0;
+
+    for line in text.split_inclusive('\n') {
+        if !line.trim().is_empty() {
+            start.get_or_insert(offset);
+            end = offset + line.len();
+        }
+        offset += line.len();
+    }
+
+    match start {
+        Some(start) => &text[start..end],
+        None => "",
+    }
+}
+
+/// Find bare `http://` and `https://` URLs in plain text
+///
+/// Returns the byte ranges of each URL found. A URL runs until the next
+/// whitespace character, and trailing punctuation such as a sentence-ending
+/// period is not considered part of it.
+///
+/// # Arguments
+///
+/// * `text` - The text to scan
+///
+/// # Example
+///
+/// ```
+/// let text = "See https://example.com.";
+/// let urls = pulldown_html_ext::utils::find_bare_urls(text);
+/// assert_eq!(&text[urls[0].clone()], "https://example.com");
+/// ```
+pub fn find_bare_urls(text: &str) -> Vec<Range<usize>> {
+    let mut urls = Vec::new();
+    let mut pos = 0;
+
+    while let Some(found) = text[pos..].find("http") {
+        let start = pos + found;
+        let rest = &text[start..];
+        let scheme_len = if rest.starts_with("https://") {
+            "https://".len()
+        } else if rest.starts_with("http://") {
+            "http://".len()
+        } else {
+            pos = start + "http".len();
+            continue;
+        };
+
+        // Only match at a word boundary, so "xhttp://" is left alone
+        let at_boundary =
+            !matches!(text[..start].chars().next_back(), Some(c) if c.is_alphanumeric());
+
+        let len = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"'))
+            .unwrap_or(rest.len());
+        let url = trim_url_end(&rest[..len]);
+
+        if at_boundary && url.len() > scheme_len {
+            urls.push(start..start + url.len());
+        }
+        pos = start + len.max(scheme_len);
+    }
+    urls
+}
+
+/// Strip trailing punctuation from a bare URL, keeping a closing parenthesis
+/// that balances an opening one in the URL, as GFM autolinks do
+fn trim_url_end(url: &str) -> &str {
+    let mut url = url;
+    loop {
+        let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?', '\'']);
+        match trimmed.strip_suffix(')') {
+            Some(stripped) if trimmed.matches(')').count() > trimmed.matches('(').count() => {
+                url = stripped;
+            }
+            _ => return trimmed,
+        }
+    }
+}
+
+/// Find pandoc-style superscript (`^...^`) and subscript (`~...~`) spans in plain text
+///
+/// Returns the delimiter character and the byte range of each span,
+/// including its delimiters. Spans must be non-empty and contain no
+/// whitespace, and doubled tildes (`~~`) are never treated as delimiters.
+///
+/// # Arguments
+///
+/// * `text` - The text to scan
+///
+/// # Example
+///
+/// ```
+/// let text = "H~2~O and x^2^";
+/// let spans = pulldown_html_ext::utils::find_sup_sub(text);
+/// assert_eq!(spans, vec![('~', 1..4), ('^', 11..14)]);
+/// ```
+pub fn find_sup_sub(text: &str) -> Vec<(char, Range<usize>)> {
+    let bytes = text.as_bytes();
+    let is_delim = |i: usize, d: u8| bytes.get(i) == Some(&d);
+    let mut spans = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let delim = bytes[pos];
+        let doubled = is_delim(pos + 1, delim) || (pos > 0 && is_delim(pos - 1, delim));
+        if !matches!(delim, b'^' | b'~') || doubled {
+            pos += 1;
+            continue;
+        }
+
+        let start = pos;
+        let content_len = text[start + 1..]
+            .find(|c: char| c.is_whitespace() || c == delim as char)
+            .filter(|&len| len > 0 && is_delim(start + 1 + len, delim));
+        match content_len {
+            Some(len) if !is_delim(start + len + 2, delim) => {
+                let end = start + len + 2;
+                spans.push((delim as char, start..end));
+                pos = end;
+            }
+            _ => pos += 1,
+        }
+    }
+    spans
+}
+
+/// Find ISO 8601 calendar dates like `2024-01-15` in plain text
+///
+/// Matching is conservative: the month and day must be in range, and a date
+/// directly adjacent to letters, digits, `-` or `/`, or preceded by `.` or `:`
+/// (as in version strings, paths or timestamps) is not matched.
+///
+/// # Arguments
+///
+/// * `text` - The text to scan
+///
+/// # Example
+///
+/// ```
+/// let text = "Released 2024-01-15, not 2024-13-01 or v2024-01-15";
+/// let dates = pulldown_html_ext::utils::find_iso_dates(text);
+/// assert_eq!(dates, vec![9..19]);
+/// ```
+pub fn find_iso_dates(text: &str) -> Vec<Range<usize>> {
+    let bytes = text.as_bytes();
+    let bounds_before =
+        |b: u8| !(b.is_ascii_alphanumeric() || matches!(b, b'-' | b'/' | b':' | b'.'));
+    let bounds_after = |b: u8| !(b.is_ascii_alphanumeric() || matches!(b, b'-' | b'/'));
+    let number = |range: Range<usize>| -> u32 {
+        bytes[range]
+            .iter()
+            .fold(0, |n, b| n * 10 + (b - b'0') as u32)
+    };
+
+    let mut dates = Vec::new();
+    let mut start = 0;
+    while start + 10 <= bytes.len() {
+        let candidate = &bytes[start..start + 10];
+        let shape = candidate.iter().enumerate().all(|(i, &b)| match i {
+            4 | 7 => b == b'-',
+            _ => b.is_ascii_digit(),
+        });
+        let bounded = (start == 0 || bounds_before(bytes[start - 1]))
+            && bytes.get(start + 10).is_none_or(|&b| bounds_after(b));
+        if shape && bounded {
+            let month = number(start + 5..start + 7);
+            let day = number(start + 8..start + 10);
+            if (1..=12).contains(&month) && (1..=31).contains(&day) {
+                dates.push(start..start + 10);
+                start += 10;
+                continue;
+            }
+        }
+        start += 1;
+    }
+    dates
+}
+
+/// Shortcodes known to [`emoji_for_shortcode`], sorted by name
+const EMOJI: &[(&str, &str)] = &[
+    ("+1", "👍"),
+    ("-1", "👎"),
+    ("100", "💯"),
+    ("bug", "🐛"),
+    ("clap", "👏"),
+    ("coffee", "☕"),
+    ("construction", "🚧"),
+    ("cry", "😢"),
+    ("eyes", "👀"),
+    ("fire", "🔥"),
+    ("grin", "😁"),
+    ("grinning", "😀"),
+    ("heart", "❤️"),
+    ("heavy_check_mark", "✔️"),
+    ("hourglass", "⌛"),
+    ("joy", "😂"),
+    ("laughing", "😆"),
+    ("memo", "📝"),
+    ("ok_hand", "👌"),
+    ("package", "📦"),
+    ("pray", "🙏"),
+    ("question", "❓"),
+    ("rocket", "🚀"),
+    ("sad", "😞"),
+    ("smile", "😄"),
+    ("smiley", "😃"),
+    ("sparkles", "✨"),
+    ("star", "⭐"),
+    ("sunglasses", "😎"),
+    ("tada", "🎉"),
+    ("thinking", "🤔"),
+    ("thumbsdown", "👎"),
+    ("thumbsup", "👍"),
+    ("warning", "⚠️"),
+    ("wave", "👋"),
+    ("white_check_mark", "✅"),
+    ("wink", "😉"),
+    ("x", "❌"),
+    ("zap", "⚡"),
+];
+
+/// Look up the emoji for a shortcode name, such as `smile` for `:smile:`
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(pulldown_html_ext::utils::emoji_for_shortcode("tada"), Some("🎉"));
+/// assert_eq!(pulldown_html_ext::utils::emoji_for_shortcode("nope"), None);
+/// ```
+pub fn emoji_for_shortcode(name: &str) -> Option<&'static str> {
+    EMOJI
+        .binary_search_by(|(shortcode, _)| (*shortcode).cmp(name))
+        .ok()
+        .map(|index| EMOJI[index].1)
+}
+
+/// Find known emoji shortcodes like `:smile:` in plain text
+///
+/// Returns the byte range of each shortcode, including the colons, along with
+/// its emoji. Unknown names are left alone, so text such as `12:30:45` is not
+/// affected.
+///
+/// # Arguments
+///
+/// * `text` - The text to scan
+///
+/// # Example
+///
+/// ```
+/// let text = "Shipped :rocket: at 12:30:45";
+/// let shortcodes = pulldown_html_ext::utils::find_emoji_shortcodes(text);
+/// assert_eq!(shortcodes, vec![(8..16, "🚀")]);
+/// ```
+pub fn find_emoji_shortcodes(text: &str) -> Vec<(Range<usize>, &'static str)> {
+    let mut shortcodes = Vec::new();
+    let mut pos = 0;
+    while let Some(found) = text[pos..].find(':') {
+        let start = pos + found;
+        let rest = &text[start + 1..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-')))
+            .unwrap_or(rest.len());
+        if rest[len..].starts_with(':') {
+            if let Some(emoji) = emoji_for_shortcode(&rest[..len]) {
+                let end = start + len + 2;
+                shortcodes.push((start..end, emoji));
+                pos = end;
+                continue;
+            }
+        }
+        pos = start + 1;
+    }
+    shortcodes
+}
+
+/// A code fence info string split into its language and `key=value` attributes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FenceInfo {
+    /// The first word of the info string
+    pub language: Option<String>,
+    /// Attributes following the language, in order of appearance
+    pub attributes: Vec<(String, String)>,
+}
+
+impl FenceInfo {
+    /// The value of the first attribute with the given key
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Parse a code fence info string such as `rust title="src/main.rs"`
+///
+/// Values may be bare words or wrapped in single or double quotes. Keys must
+/// start with an ASCII letter and contain only ASCII letters, digits, `-` and
+/// `_`; other pairs and words without a value are skipped. Values are returned
+/// unescaped and must be escaped when written to HTML.
+///
+/// # Arguments
+///
+/// * `info` - The info string following the opening fence
+///
+/// # Example
+///
+/// ```
+/// let info = pulldown_html_ext::utils::parse_fence_info("rust title=\"src/main.rs\" hl=1");
+/// assert_eq!(info.language.as_deref(), Some("rust"));
+/// assert_eq!(info.get("title"), Some("src/main.rs"));
+/// assert_eq!(info.get("hl"), Some("1"));
+/// ```
+pub fn parse_fence_info(info: &str) -> FenceInfo {
+    let info = info.trim();
+    let (language, mut rest) = match info.find(char::is_whitespace) {
+        Some(end) => (&info[..end], &info[end..]),
+        None => (info, ""),
+    };
+
+    let mut attributes = Vec::new();
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+
+        let key_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let key = &rest[..key_end];
+        rest = &rest[key_end..];
+        let Some(value_start) = rest.strip_prefix('=') else {
+            continue;
+        };
+
+        let (value, remainder) = match value_start.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let quoted = &value_start[1..];
+                match quoted.find(quote) {
+                    Some(end) => (&quoted[..end], &quoted[end + 1..]),
+                    None => (quoted, ""),
+                }
+            }
+            _ => {
+                let end = value_start
+                    .find(char::is_whitespace)
+                    .unwrap_or(value_start.len());
+                (&value_start[..end], &value_start[end..])
+            }
+        };
+        rest = remainder;
+
+        if is_valid_attribute_key(key) {
+            attributes.push((key.to_string(), value.to_string()));
+        }
+    }
+
+    FenceInfo {
+        language: (!language.is_empty()).then(|| language.to_string()),
+        attributes,
+    }
+}
+
+fn is_valid_attribute_key(key: &str) -> bool {
+    key.starts_with(|c: char| c.is_ascii_alphabetic())
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Join class names into a single `class` attribute value
+///
+/// Each entry may itself hold several space-separated names. With `dedupe`
+/// set, names that already appeared are dropped, keeping the first occurrence.
+///
+/// # Arguments
+///
+/// * `classes` - The class names to join
+/// * `dedupe` - Whether to drop repeated names
+///
+/// # Example
+///
+/// ```
+/// let classes = pulldown_html_ext::utils::join_classes(["title", "title main", "main"], true);
+/// assert_eq!(classes, "title main");
+/// ```
+pub fn join_classes<'a, I>(classes: I, dedupe: bool) -> String
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut names: Vec<&str> = Vec::new();
+    for name in classes.into_iter().flat_map(str::split_whitespace) {
+        if !dedupe || !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names.join(" ")
+}
+
 /// Count the length of a string in Unicode scalars
 ///
 /// This is useful for generating heading IDs and other cases
@@ -136,6 +692,136 @@ mod tests {
         assert_eq!(sanitize_id("--multiple---dashes--"), "multiple-dashes");
     }
 
+    #[test]
+    fn test_normalize_whitespace() {
+        assert_eq!(normalize_whitespace("one\ntwo"), "one two");
+        assert_eq!(normalize_whitespace("  padded \t text\r\n"), "padded text");
+        assert_eq!(normalize_whitespace("single"), "single");
+        assert_eq!(normalize_whitespace(""), "");
+    }
+
+    #[test]
+    fn test_split_title_dimensions() {
+        assert_eq!(split_title_dimensions("=16x9"), ("", Some((16, 9))));
+        assert_eq!(
+            split_title_dimensions("A logo =16x9 "),
+            ("A logo", Some((16, 9)))
+        );
+        assert_eq!(split_title_dimensions("A logo"), ("A logo", None));
+        assert_eq!(split_title_dimensions("x =16xwide"), ("x =16xwide", None));
+        assert_eq!(split_title_dimensions("a=16x9"), ("a=16x9", None));
+    }
+
+    #[test]
+    fn test_trim_blank_lines() {
+        assert_eq!(trim_blank_lines("\n\ncode\n\n"), "code\n");
+        assert_eq!(trim_blank_lines(" \t\na\n\nb\n \n"), "a\n\nb\n");
+        assert_eq!(trim_blank_lines("  indented\n"), "  indented\n");
+        assert_eq!(trim_blank_lines("no newline"), "no newline");
+        assert_eq!(trim_blank_lines("\n \n"), "");
+    }
+
+    #[test]
+    fn test_find_bare_urls() {
+        let text = "Visit https://example.com/docs, or http://a.b/c?d=1.";
+        let urls: Vec<&str> = find_bare_urls(text)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect();
+        assert_eq!(urls, vec!["https://example.com/docs", "http://a.b/c?d=1"]);
+
+        assert!(find_bare_urls("no links here").is_empty());
+        assert!(find_bare_urls("just https:// alone").is_empty());
+        assert!(find_bare_urls("xhttps://example.com").is_empty());
+    }
+
+    #[test]
+    fn test_find_sup_sub() {
+        assert_eq!(find_sup_sub("H~2~O"), vec![('~', 1..4)]);
+        assert_eq!(
+            find_sup_sub("2^10^ and e^x^"),
+            vec![('^', 1..5), ('^', 11..14)]
+        );
+
+        assert!(find_sup_sub("a ~~struck~~ b").is_empty());
+        assert!(find_sup_sub("x^ 2^ and ^^").is_empty());
+        assert!(find_sup_sub("unclosed ^sup").is_empty());
+    }
+
+    #[test]
+    fn test_encode_non_ascii() {
+        let mut output = String::new();
+        encode_non_ascii(&mut output, "naïve &#233; 😀!");
+        assert_eq!(output, "na&#239;ve &#233; &#128512;!");
+    }
+
+    #[test]
+    fn test_escape_html_keep_entities() {
+        let mut output = String::new();
+        escape_html_keep_entities(&mut output, "&amp; &#39; &#x27; &nbsp; & &; &#; &#xg; a&b");
+        assert_eq!(
+            output,
+            "&amp; &#39; &#x27; &nbsp; &amp; &amp;; &amp;#; &amp;#xg; a&amp;b"
+        );
+
+        // References are only looked for within the longest possible name
+        let mut output = String::new();
+        let long = format!("&{}; &CounterClockwiseContourIntegral;", "a".repeat(32));
+        escape_html_keep_entities(&mut output, &long);
+        assert_eq!(
+            output,
+            format!("&amp;{}; &CounterClockwiseContourIntegral;", "a".repeat(32))
+        );
+    }
+
+    #[test]
+    fn test_emoji_table_sorted() {
+        assert!(EMOJI.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn test_find_emoji_shortcodes() {
+        assert_eq!(
+            find_emoji_shortcodes(":+1::tada: :nope: ::smile:"),
+            vec![(0..4, "👍"), (4..10, "🎉"), (19..26, "😄")]
+        );
+        assert!(find_emoji_shortcodes("a:b:c 10:00").is_empty());
+    }
+
+    #[test]
+    fn test_find_iso_dates() {
+        assert_eq!(find_iso_dates("2024-02-29"), vec![0..10]);
+        assert_eq!(find_iso_dates("On 2024-02-29."), vec![3..13]);
+        assert!(find_iso_dates("2024-00-10 2024-01-32 12024-01-01").is_empty());
+        assert!(find_iso_dates("2024-01-15T10:00 /2024-01-15 1.2024-01-15").is_empty());
+        assert_eq!(
+            find_iso_dates("(1999-12-31 to 2000-01-01)"),
+            vec![1..11, 15..25]
+        );
+    }
+
+    #[test]
+    fn test_parse_fence_info() {
+        assert_eq!(parse_fence_info(""), FenceInfo::default());
+
+        let info = parse_fence_info("  python  ");
+        assert_eq!(info.language.as_deref(), Some("python"));
+        assert!(info.attributes.is_empty());
+
+        let info = parse_fence_info(
+            "rust title='a \"b\"' linenos on\"click=x bad key=1 data-x=\"unterminated",
+        );
+        assert_eq!(info.language.as_deref(), Some("rust"));
+        assert_eq!(
+            info.attributes,
+            vec![
+                ("title".to_string(), "a \"b\"".to_string()),
+                ("key".to_string(), "1".to_string()),
+                ("data-x".to_string(), "unterminated".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn test_unicode_length() {
         assert_eq!(unicode_length("Hello"), 5);
@@ -1,7 +1,8 @@
 use html_compare_rs::assert_html_eq;
-use pulldown_cmark::Parser;
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
 use pulldown_html_ext::*;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 fn render_with_config(input: &str, config: &HtmlConfig) -> String {
     let mut output = String::new();
@@ -11,24 +12,34 @@ fn render_with_config(input: &str, config: &HtmlConfig) -> String {
     output
 }
 
+fn render_with_options(input: &str, options: Options, config: &HtmlConfig) -> String {
+    let mut output = String::new();
+    let handler = DefaultHtmlWriter::new(&mut output, config.clone());
+    let mut renderer = HtmlRenderer::new(handler);
+    let _ = renderer.run(Parser::new_ext(input, options));
+    output
+}
+
 // Individual HTML options tests
 #[test]
-#[ignore = "TODO: Fix/define escape_html handling in renderer"]
 fn test_escape_html_option() {
+    let input = "<div>Tom &amp; Jerry</div>\n\nSee <b>this</b> & that";
     let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
 
-    // With HTML escaping (default)
+    // Raw HTML is escaped, keeping the character references already in it
     config.html.escape_html = true;
-    assert_html_eq!(
-        render_with_config("<div>test</div>", &config),
-        "<p>&lt;div&gt;test&lt;/div&gt;</p>"
+    assert_eq!(
+        render_with_config(input, &config),
+        "&lt;div&gt;Tom &amp; Jerry&lt;/div&gt;\n\
+         <p>See &lt;b&gt;this&lt;/b&gt; &amp; that</p>"
     );
 
-    // Without HTML escaping
+    // Without HTML escaping (default) raw HTML passes through
     config.html.escape_html = false;
-    assert_html_eq!(
-        render_with_config("<div>test</div>", &config),
-        "<p><div>test</div></p>"
+    assert_eq!(
+        render_with_config(input, &config),
+        "<div>Tom &amp; Jerry</div>\n<p>See <b>this</b> & that</p>"
     );
 }
 
@@ -70,6 +81,59 @@ fn test_xhtml_style_option() {
     );
 }
 
+#[test]
+fn test_autolink_bare_urls_option() {
+    let mut config = HtmlConfig::default();
+    config.html.autolink_bare_urls = true;
+    config.elements.links.nofollow_external = false;
+    config.elements.links.open_external_blank = false;
+
+    assert_html_eq!(
+        render_with_config("Visit https://example.com/docs for more", &config),
+        "<p>Visit <a href=\"https://example.com/docs\">https://example.com/docs</a> for more</p>"
+    );
+
+    // Trailing punctuation is not part of the URL
+    assert_html_eq!(
+        render_with_config("See http://example.com.", &config),
+        "<p>See <a href=\"http://example.com\">http://example.com</a>.</p>"
+    );
+
+    // URLs are found across the points where the parser splits text
+    for (input, url) in [
+        ("https://example.com/a[1]", "https://example.com/a%5B1%5D"),
+        ("https://example.com/a&amp;b", "https://example.com/a&amp;b"),
+        ("https://example.com/a\\_b", "https://example.com/a_b"),
+    ] {
+        assert!(render_with_config(input, &config).contains(&format!("<a href=\"{url}\">")));
+    }
+    assert!(render_with_options(
+        "https://example.com/~user/x",
+        Options::ENABLE_STRIKETHROUGH,
+        &config
+    )
+    .contains("<a href=\"https://example.com/~user/x\">"));
+
+    // A closing parenthesis is only part of the URL when it is balanced
+    assert_html_eq!(
+        render_with_config("(see http://a.b/(c)) and http://a.b/(c).", &config),
+        "<p>(see <a href=\"http://a.b/(c)\">http://a.b/(c)</a>) and \
+         <a href=\"http://a.b/(c)\">http://a.b/(c)</a>.</p>"
+    );
+
+    // Code spans and existing links are left alone
+    assert_html_eq!(
+        render_with_config("`https://example.com` [https://x.org](/local)", &config),
+        "<p><code>https://example.com</code> <a href=\"/local\">https://x.org</a></p>"
+    );
+
+    config.html.autolink_bare_urls = false;
+    assert_html_eq!(
+        render_with_config("See http://example.com.", &config),
+        "<p>See http://example.com.</p>"
+    );
+}
+
 // Individual element options tests
 #[test]
 fn test_heading_id_option() {
@@ -132,6 +196,42 @@ fn test_link_options() {
     );
 }
 
+#[test]
+fn test_normalize_titles_option() {
+    let input = "[Link][ref] and ![Image][ref]\n\n[ref]: /target \"A title\nover  lines\"";
+    let mut config = HtmlConfig::default();
+
+    // Without normalization the newline is kept inside the attribute
+    config.elements.links.normalize_titles = false;
+    assert!(render_with_config(input, &config).contains("title=\"A title\nover  lines\""));
+
+    // With normalization internal whitespace collapses to single spaces
+    config.elements.links.normalize_titles = true;
+    assert_html_eq!(
+        render_with_config(input, &config),
+        "<p><a href=\"/target\" title=\"A title over lines\">Link</a> and \
+             <img src=\"/target\" alt=\"Image\" title=\"A title over lines\"></p>"
+    );
+}
+
+#[test]
+fn test_image_link_to_source_option() {
+    let mut config = HtmlConfig::default();
+    config.elements.images.link_to_source = true;
+
+    assert_html_eq!(
+        render_with_config("![Alt](image.jpg)", &config),
+        "<p><a href=\"image.jpg\"><img src=\"image.jpg\" alt=\"Alt\"></a></p>"
+    );
+
+    // External images follow the configured external link behaviour
+    assert_html_eq!(
+        render_with_config("![Alt](https://example.com/image.jpg)", &config),
+        "<p><a href=\"https://example.com/image.jpg\" rel=\"nofollow\" target=\"_blank\">\
+             <img src=\"https://example.com/image.jpg\" alt=\"Alt\"></a></p>"
+    );
+}
+
 #[test]
 fn test_code_block_options() {
     let mut config = HtmlConfig::default();
@@ -151,27 +251,1808 @@ fn test_code_block_options() {
 }
 
 #[test]
-fn test_custom_attributes() {
+fn test_list_first_last_classes() {
     let mut config = HtmlConfig::default();
-    let mut element_attributes = HashMap::new();
+    config.elements.lists.mark_first_last = true;
 
-    // Add attributes for paragraphs
-    let mut p_attrs = HashMap::new();
-    p_attrs.insert("class".to_string(), "content".to_string());
-    element_attributes.insert("p".to_string(), p_attrs);
+    assert_html_eq!(
+        render_with_config("* One\n* Two\n* Three", &config),
+        "<ul><li class=\"first\">One</li><li>Two</li><li class=\"last\">Three</li></ul>"
+    );
 
-    // Add attributes for code blocks
-    let mut pre_attrs = HashMap::new();
-    pre_attrs.insert("data-type".to_string(), "code".to_string());
-    element_attributes.insert("pre".to_string(), pre_attrs);
+    // Nested lists track their own first and last items
+    assert_html_eq!(
+        render_with_config("1. One\n    * A\n2. Two", &config),
+        "<ol><li class=\"first\">One<ul><li class=\"first last\">A</li></ul></li>\
+             <li class=\"last\">Two</li></ol>"
+    );
+}
 
-    config.attributes.element_attributes = element_attributes;
+#[test]
+fn test_math_rendering() {
+    let input = "Inline $x^2$ math\n\n$$\\sum x$$";
+    let mut config = HtmlConfig::default();
 
     assert_html_eq!(
-        render_with_config("Regular paragraph\n\n```\nCode block\n```", &config),
-        "<p class=\"content\">Regular paragraph</p>\
-             <pre data-type=\"code\"><code>Code block</code></pre>"
+        render_with_options(input, Options::ENABLE_MATH, &config),
+        "<p>Inline <span class=\"math math-inline\">x^2</span> math</p>\
+         <p><span class=\"math math-display\">\\sum x</span></p>"
+    );
+
+    config.hooks.math_renderer = Some(Arc::new(|tex: &str, display: bool| {
+        let tag = if display { "katex-display" } else { "katex" };
+        format!("<{tag}>{tex}</{tag}>")
+    }));
+    assert_html_eq!(
+        render_with_options(input, Options::ENABLE_MATH, &config),
+        "<p>Inline <katex>x^2</katex> math</p><p><katex-display>\\sum x</katex-display></p>"
+    );
+}
+
+#[test]
+fn test_unknown_event_policy() {
+    let input = "---\ntitle: A -- B\n---\n\nHello";
+    let options = Options::ENABLE_YAML_STYLE_METADATA_BLOCKS;
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+
+    // Metadata blocks have no HTML mapping
+    assert_eq!(render_with_options(input, options, &config), "<p>Hello</p>");
+
+    config.html.unknown_event_policy = UnknownPolicy::Comment;
+    assert_eq!(
+        render_with_options(input, options, &config),
+        "<!-- title: A - - B\n --><p>Hello</p>"
+    );
+
+    config.html.unknown_event_policy = UnknownPolicy::Error;
+    let mut output = String::new();
+    assert!(matches!(
+        push_html(&mut output, Parser::new_ext(input, options), &config),
+        Err(HtmlError::Render(_))
+    ));
+}
+
+#[test]
+fn test_unknown_event_policy_with_offsets() {
+    let input = "---\ntitle: *A*\n---\n\nHello";
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.html.unknown_event_policy = UnknownPolicy::Comment;
+
+    // The comment holds the block's original source, delimiters included
+    let mut output = String::new();
+    let handler = DefaultHtmlWriter::new(&mut output, config);
+    let mut renderer = HtmlRenderer::new(handler);
+    let parser = Parser::new_ext(input, Options::ENABLE_YAML_STYLE_METADATA_BLOCKS);
+    renderer
+        .run_with_offsets(input, parser.into_offset_iter())
+        .unwrap();
+    assert_eq!(output, "<!-- - - -\ntitle: *A*\n- - - --><p>Hello</p>");
+}
+
+#[test]
+fn test_table_header_scopes() {
+    let input = "| A | B |\n|---|---|\n| 1 | 2 |\n| 3 | 4 |";
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.tables.header_scopes = true;
+
+    assert_eq!(
+        render_with_options(input, Options::ENABLE_TABLES, &config),
+        "<table><thead><tr><th scope=\"col\">A</th><th scope=\"col\">B</th></tr></thead>\
+         <tbody><tr><th scope=\"row\">1</th><td>2</td></tr>\
+         <tr><th scope=\"row\">3</th><td>4</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn test_list_explicit_values() {
+    let mut config = HtmlConfig::default();
+    config.elements.lists.explicit_values = true;
+
+    assert_html_eq!(
+        render_with_config("3. a\n5. b", &config),
+        "<ol start=\"3\"><li value=\"3\">a</li><li value=\"4\">b</li></ol>"
+    );
+
+    // Unordered lists, including ones nested in ordered lists, get no values
+    assert_html_eq!(
+        render_with_config("1. a\n    * x\n2. b", &config),
+        "<ol><li value=\"1\">a<ul><li>x</li></ul></li><li value=\"2\">b</li></ol>"
+    );
+}
+
+#[test]
+fn test_unordered_list_marker() {
+    let mut config = HtmlConfig::default();
+    config.elements.lists.unordered_marker = Some("arrow".to_string());
+
+    assert_html_eq!(
+        render_with_config("* a\n* b", &config),
+        "<ul data-marker=\"arrow\"><li>a</li><li>b</li></ul>"
+    );
+
+    // Ordered lists are unaffected
+    assert_html_eq!(
+        render_with_config("1. a\n2. b", &config),
+        "<ol><li>a</li><li>b</li></ol>"
+    );
+}
+
+#[test]
+fn test_code_span_kbd_language() {
+    let mut config = HtmlConfig::default();
+    config.elements.code_spans.kbd_language = Some("kbd".to_string());
+
+    assert_html_eq!(
+        render_with_config("Press `kbd:Ctrl+C` to copy `kbdx`", &config),
+        "<p>Press <kbd>Ctrl+C</kbd> to copy <code>kbdx</code></p>"
+    );
+}
+
+#[test]
+fn test_heading_id_max_level() {
+    let mut config = HtmlConfig::default();
+    config.elements.headings.id_max_level = 3;
+
+    assert_html_eq!(
+        render_with_config("### Three\n\n#### Four", &config),
+        "<h3 id=\"heading-3\">Three</h3><h4>Four</h4>"
+    );
+}
+
+#[test]
+fn test_paragraph_preserve_newlines() {
+    let mut config = HtmlConfig::default();
+    config.elements.paragraphs.preserve_newlines = true;
+
+    assert_html_eq!(
+        render_with_config("Roses are red,\nviolets are blue", &config),
+        "<p class=\"preserve-newlines\">Roses are red,\nviolets are blue</p>"
+    );
+}
+
+#[test]
+fn test_heading_id_source() {
+    let input = "# Explicit {#x}\n\n# Plain";
+    let mut config = HtmlConfig::default();
+
+    config.elements.headings.id_source = IdSource::ExplicitThenGenerated;
+    assert_html_eq!(
+        render_with_options(input, Options::ENABLE_HEADING_ATTRIBUTES, &config),
+        "<h1 id=\"x\">Explicit</h1><h1 id=\"heading-1\">Plain</h1>"
+    );
+
+    config.elements.headings.id_source = IdSource::GeneratedOnly;
+    assert_html_eq!(
+        render_with_options(input, Options::ENABLE_HEADING_ATTRIBUTES, &config),
+        "<h1 id=\"heading-1\">Explicit</h1><h1 id=\"heading-1\">Plain</h1>"
+    );
+
+    config.elements.headings.id_source = IdSource::ExplicitOnly;
+    assert_html_eq!(
+        render_with_options(input, Options::ENABLE_HEADING_ATTRIBUTES, &config),
+        "<h1 id=\"x\">Explicit</h1><h1>Plain</h1>"
+    );
+}
+
+#[test]
+fn test_extract_headings_matches_rendering() {
+    let input = "# Intro\n\n## Setup {#setup}\n\n## Usage *now*\n\n## Again\n\n# Intro";
+    let options = Options::ENABLE_HEADING_ATTRIBUTES;
+    let mut config = HtmlConfig::default();
+    let rendered_ids = |html: &str| -> Vec<String> {
+        html.split("id=\"")
+            .skip(1)
+            .map(|rest| rest[..rest.find('"').unwrap()].to_string())
+            .collect()
+    };
+
+    let headings = extract_headings(input, &config).unwrap();
+    let texts: Vec<&str> = headings.iter().map(|h| h.text.as_str()).collect();
+    assert_eq!(texts, ["Intro", "Setup", "Usage now", "Again", "Intro"]);
+
+    // Generated IDs are shared by headings of the same level by default
+    let extracted: Vec<String> = headings.iter().filter_map(|h| h.id.clone()).collect();
+    assert_eq!(
+        extracted,
+        ["heading-1", "setup", "heading-2", "heading-2", "heading-1"]
+    );
+    assert_eq!(
+        rendered_ids(&render_with_options(input, options, &config)),
+        extracted
+    );
+
+    // Suffixed IDs match the rendered ones too
+    config.elements.headings.on_duplicate_id = DupPolicy::Suffix;
+    let extracted: Vec<String> = extract_headings(input, &config)
+        .unwrap()
+        .into_iter()
+        .filter_map(|h| h.id)
+        .collect();
+    assert_eq!(
+        extracted,
+        [
+            "heading-1",
+            "setup",
+            "heading-2",
+            "heading-2-1",
+            "heading-1-1"
+        ]
+    );
+    assert_eq!(
+        rendered_ids(&render_with_options(input, options, &config)),
+        extracted
+    );
+
+    // IDs taken by other elements are accounted for
+    let input = "Setup\n\n# Setup\n\nUsage\n\n## Usage";
+    config.elements.headings.id_prefix = String::new();
+    config.elements.headings.id_from_content = true;
+    config.hooks.id_generator = Some(Arc::new(|tag: &str| {
+        (tag == "p").then(|| "usage".to_string())
+    }));
+
+    let extracted: Vec<String> = extract_headings(input, &config)
+        .unwrap()
+        .into_iter()
+        .filter_map(|h| h.id)
+        .collect();
+    assert_eq!(extracted, ["setup", "usage-2"]);
+    assert_eq!(
+        rendered_ids(&render_with_options(input, options, &config)),
+        ["usage", "setup", "usage-1", "usage-2"]
+    );
+}
+
+#[test]
+fn test_line_ending_crlf() {
+    let mut config = HtmlConfig::default();
+    config.html.break_on_newline = false;
+
+    assert_eq!(
+        render_with_config("# Title\n\nfirst\nsecond", &config),
+        "<h1 id=\"heading-1\">Title</h1>\n<p>first\nsecond</p>\n"
+    );
+
+    config.html.line_ending = LineEnding::CrLf;
+    assert_eq!(
+        render_with_config("# Title\n\nfirst\nsecond", &config),
+        "<h1 id=\"heading-1\">Title</h1>\r\n<p>first\r\nsecond</p>\r\n"
+    );
+
+    config.html.pretty_print = false;
+    assert_eq!(
+        render_with_config("# Title\n\n---", &config),
+        "<h1 id=\"heading-1\">Title</h1><hr>"
+    );
+}
+
+#[test]
+fn test_definition_list_multiple_definitions() {
+    let mut config = HtmlConfig::default();
+    config.elements.definition_lists.class = Some("glossary".to_string());
+
+    assert_html_eq!(
+        render_with_options(
+            "Term\n: First definition\n: Second definition",
+            Options::ENABLE_DEFINITION_LIST,
+            &config
+        ),
+        "<dl class=\"glossary\"><dt>Term</dt>\
+         <dd>First definition</dd><dd>Second definition</dd></dl>"
+    );
+}
+
+#[test]
+fn test_definition_list_term_ids() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.definition_lists.term_ids = true;
+
+    assert_eq!(
+        render_with_options(
+            "# Crate {#crate}\n\n*Crate*\n: A package\n\nTrait object\n: A dynamic type",
+            Options::ENABLE_DEFINITION_LIST | Options::ENABLE_HEADING_ATTRIBUTES,
+            &config
+        ),
+        "<h1 id=\"crate\">Crate</h1><dl><dt id=\"crate-1\"><em>Crate</em></dt><dd>A package</dd>\
+         <dt id=\"trait-object\">Trait object</dt><dd>A dynamic type</dd></dl>"
+    );
+}
+
+#[test]
+fn test_schema_org_microdata() {
+    let mut config = HtmlConfig::default();
+    config.html.schema_org = true;
+
+    assert_html_eq!(
+        render_with_config("# First\n\n# Second", &config),
+        "<article itemscope itemtype=\"https://schema.org/Article\">\
+         <h1 id=\"heading-1\" itemprop=\"headline\">First</h1>\
+         <h1 id=\"heading-1\">Second</h1></article>"
+    );
+}
+
+#[test]
+fn test_empty_href_policy() {
+    let mut config = HtmlConfig::default();
+
+    config.elements.links.empty_href_policy = EmptyHref::Keep;
+    assert_html_eq!(
+        render_with_config("[x]()", &config),
+        "<p><a href=\"\">x</a></p>"
+    );
+
+    config.elements.links.empty_href_policy = EmptyHref::Drop;
+    assert_html_eq!(render_with_config("[*x*]()", &config), "<p><em>x</em></p>");
+
+    config.elements.links.empty_href_policy = EmptyHref::Placeholder("#".to_string());
+    assert_html_eq!(
+        render_with_config("[x]()", &config),
+        "<p><a href=\"#\">x</a></p>"
+    );
+}
+
+#[test]
+fn test_heading_ids_from_content() {
+    let input = "## *Important* Note\n\n## `code` and **bold**";
+    let mut config = HtmlConfig::default();
+    config.elements.headings.id_prefix = String::new();
+    config.elements.headings.id_from_content = true;
+
+    assert_html_eq!(
+        render_with_config(input, &config),
+        "<h2 id=\"important-note\"><em>Important</em> Note</h2>\
+         <h2 id=\"code-and-bold\"><code>code</code> and <strong>bold</strong></h2>"
+    );
+
+    let ids: Vec<Option<String>> = extract_headings(input, &config)
+        .unwrap()
+        .into_iter()
+        .map(|heading| heading.id)
+        .collect();
+    assert_eq!(
+        ids,
+        [
+            Some("important-note".to_string()),
+            Some("code-and-bold".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_code_block_class_prefix() {
+    let input = "```rust\nfn main() {}\n```";
+    let mut config = HtmlConfig::default();
+
+    assert_html_eq!(
+        render_with_config(input, &config),
+        "<pre><code class=\"language-rust\">fn main() {}\n</code></pre>"
+    );
+
+    config.elements.code_blocks.class_prefix = "lang-".to_string();
+    assert_html_eq!(
+        render_with_config(input, &config),
+        "<pre><code class=\"lang-rust\">fn main() {}\n</code></pre>"
+    );
+
+    config.elements.code_blocks.class_prefix = String::new();
+    assert_html_eq!(
+        render_with_config(input, &config),
+        "<pre><code class=\"rust\">fn main() {}\n</code></pre>"
+    );
+}
+
+#[test]
+fn test_code_block_copy_button() {
+    let input = "```rust\nlet a = 1;\n```\n\ntext\n\n```\nplain\n```";
+    let mut config = HtmlConfig::default();
+    config.elements.code_blocks.copy_button = true;
+
+    let output = render_with_config(input, &config);
+    assert_eq!(output.matches("<button").count(), 2);
+    assert_html_eq!(
+        output,
+        "<div class=\"code-block\"><button class=\"copy-code\" aria-label=\"Copy\"></button>\
+         <pre><code class=\"language-rust\">let a = 1;\n</code></pre></div>\
+         <p>text</p>\
+         <div class=\"code-block\"><button class=\"copy-code\" aria-label=\"Copy\"></button>\
+         <pre><code>plain\n</code></pre></div>"
+    );
+}
+
+#[test]
+fn test_table_max_columns() {
+    let input = "| A | B | C |\n|---|---|---|\n| 1 | 2 | 3 |";
+    let mut config = HtmlConfig::default();
+    config.elements.tables.max_columns = Some(2);
+
+    assert_html_eq!(
+        render_with_options(input, Options::ENABLE_TABLES, &config),
+        "<table><thead><tr data-truncated=\"true\"><th>A</th><th>B</th></tr></thead>\
+         <tbody><tr data-truncated=\"true\"><td>1</td><td>2</td></tr></tbody></table>"
     );
+
+    // Tables within the limit are left alone
+    config.elements.tables.max_columns = Some(3);
+    assert_html_eq!(
+        render_with_options(input, Options::ENABLE_TABLES, &config),
+        "<table><thead><tr><th>A</th><th>B</th><th>C</th></tr></thead>\
+         <tbody><tr><td>1</td><td>2</td><td>3</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn test_attribute_provider() {
+    let mut config = HtmlConfig::default();
+    config.attributes.element_attributes.insert(
+        "h2".to_string(),
+        HashMap::from([("class".to_string(), "section".to_string())]),
+    );
+    config.hooks.attribute_provider = Some(Arc::new(|tag: &str, info: &ElementInfo| {
+        match (tag, info.heading_level, info.href) {
+            (_, Some(level), _) => vec![("data-level".to_string(), level.to_string())],
+            ("a", _, Some(href)) => vec![("data-href".to_string(), href.to_string())],
+            _ => Vec::new(),
+        }
+    }));
+
+    assert_html_eq!(
+        render_with_config("## Title\n\n[link](/x) *em*", &config),
+        "<h2 id=\"heading-2\" class=\"section\" data-level=\"2\">Title</h2>\
+         <p><a href=\"/x\" data-href=\"/x\">link</a> <em>em</em></p>"
+    );
+}
+
+#[test]
+fn test_strikethrough_tag() {
+    let mut config = HtmlConfig::default();
+
+    assert_html_eq!(
+        render_with_options("~~old~~", Options::ENABLE_STRIKETHROUGH, &config),
+        "<p><del>old</del></p>"
+    );
+
+    config.elements.strikethrough.tag = StrikeTag::S;
+    assert_html_eq!(
+        render_with_options("~~old~~", Options::ENABLE_STRIKETHROUGH, &config),
+        "<p><s>old</s></p>"
+    );
+}
+
+#[test]
+fn test_render_with_stats() {
+    let input = "# Title\n\nSee [the docs](/docs) and ![logo](logo.png).\n\n\
+                 ## Usage\n\n```rust\nfn main() {}\n```\n\n```\nmore code\n```";
+    let (html, stats) = render_with_stats(input, &HtmlConfig::default()).unwrap();
+
+    assert!(html.contains("<h2"));
+    assert_eq!(
+        stats,
+        RenderStats {
+            headings: 2,
+            links: 1,
+            images: 1,
+            code_blocks: 2,
+            words: 6,
+        }
+    );
+}
+
+#[test]
+fn test_paragraph_lead_class() {
+    let mut config = HtmlConfig::default();
+    config.elements.paragraphs.lead_class = Some("lead".to_string());
+
+    assert_html_eq!(
+        render_with_config("> Quoted\n\nIntro\n\nBody", &config),
+        "<blockquote><p>Quoted</p></blockquote><p class=\"lead\">Intro</p><p>Body</p>"
+    );
+}
+
+#[test]
+fn test_pandoc_sup_sub() {
+    let mut config = HtmlConfig::default();
+    config.html.pandoc_sup_sub = true;
+
+    assert_html_eq!(
+        render_with_config("H~2~O and x^2^", &config),
+        "<p>H<sub>2</sub>O and x<sup>2</sup></p>"
+    );
+
+    // Doubled tildes are left for strikethrough
+    assert_html_eq!(
+        render_with_config("~~gone~~ CO~2~", &config),
+        "<p>~~gone~~ CO<sub>2</sub></p>"
+    );
+    assert_html_eq!(
+        render_with_options("~~gone~~ x^2^", Options::ENABLE_STRIKETHROUGH, &config),
+        "<p><del>gone</del> x<sup>2</sup></p>"
+    );
+
+    // Single tildes the strikethrough parser splits text at are still delimiters
+    assert_html_eq!(
+        render_with_options("H~2~O and CO~2~", Options::ENABLE_STRIKETHROUGH, &config),
+        "<p>H<sub>2</sub>O and CO<sub>2</sub></p>"
+    );
+    assert_html_eq!(
+        render_with_options("a~b~ and c^d^", Options::ENABLE_STRIKETHROUGH, &config),
+        "<p>a<sub>b</sub> and c<sup>d</sup></p>"
+    );
+
+    config.html.pandoc_sup_sub = false;
+    assert_html_eq!(render_with_config("x^2^", &config), "<p>x^2^</p>");
+}
+
+#[test]
+fn test_task_list_containing_class() {
+    let mut config = HtmlConfig::default();
+    config.elements.task_lists.mark_containing_list = true;
+
+    let output = render_with_options(
+        "- [x] done\n- [ ] todo\n\ntext\n\n- plain\n  - [ ] nested",
+        Options::ENABLE_TASKLISTS,
+        &config,
+    );
+    let list_tags: Vec<&str> = output
+        .match_indices("<ul")
+        .map(|(start, _)| &output[start..start + output[start..].find('>').unwrap() + 1])
+        .collect();
+    assert_eq!(
+        list_tags,
+        [
+            "<ul class=\"contains-task-list\">",
+            "<ul>",
+            "<ul class=\"contains-task-list\">"
+        ]
+    );
+}
+
+#[test]
+fn test_code_text_transform() {
+    let mut config = HtmlConfig::default();
+    config.hooks.code_text_transform =
+        Some(Arc::new(|code: &str, lang: Option<&str>| match lang {
+            Some("sh") => code.replace("s3cr3t", "******"),
+            _ => code.to_string(),
+        }));
+
+    assert_html_eq!(
+        render_with_config(
+            "```sh\nexport TOKEN=s3cr3t\n```\n\n```\ns3cr3t\n```",
+            &config
+        ),
+        "<pre><code class=\"language-sh\">export TOKEN=******\n</code></pre>\
+         <pre><code>s3cr3t\n</code></pre>"
+    );
+}
+
+#[test]
+fn test_link_anchor_class() {
+    let mut config = HtmlConfig::default();
+    config.elements.links.anchor_class = Some("internal-anchor".to_string());
+
+    assert_html_eq!(
+        render_with_config("[a](#foo) [b](/page) [c](https://x)", &config),
+        "<p><a href=\"#foo\" class=\"internal-anchor\">a</a> \
+         <a href=\"/page\">b</a> \
+         <a href=\"https://x\" rel=\"nofollow\" target=\"_blank\">c</a></p>"
+    );
+}
+
+#[test]
+fn test_raw_html_passthrough_filter() {
+    let mut config = HtmlConfig::default();
+    config.hooks.raw_html_passthrough_filter =
+        Some(Arc::new(|html: &str| html.replace("{{ user }}", "")));
+
+    assert_html_eq!(
+        render_with_config(
+            "<div class=\"greeting\">Hello {{ user }}</div>\n\nText",
+            &config
+        ),
+        "<div class=\"greeting\">Hello </div><p>Text</p>"
+    );
+}
+
+#[test]
+fn test_heading_classes_deduplicated() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config
+        .elements
+        .headings
+        .level_classes
+        .insert(1, "title".to_string());
+    config.attributes.element_attributes.insert(
+        "h1".to_string(),
+        HashMap::from([("class".to_string(), "main title".to_string())]),
+    );
+
+    let html = render_with_options(
+        "# Intro {.title .main}",
+        Options::ENABLE_HEADING_ATTRIBUTES,
+        &config,
+    );
+    assert_eq!(html, "<h1 id=\"heading-1\" class=\"title main\">Intro</h1>");
+
+    config.attributes.dedupe_classes = false;
+    let html = render_with_options(
+        "# Intro {.title}",
+        Options::ENABLE_HEADING_ATTRIBUTES,
+        &config,
+    );
+    assert!(html.contains("class=\"title title main title\""));
+}
+
+#[test]
+fn test_configured_classes_merge() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.paragraphs.lead_class = Some("lead".to_string());
+    config.elements.lists.mark_first_last = true;
+    config.elements.tables.column_classes = vec!["num".to_string()];
+    config.elements.links.anchor_class = Some("anchor".to_string());
+    for tag in ["p", "li", "td", "th", "a"] {
+        config.attributes.element_attributes.insert(
+            tag.to_string(),
+            HashMap::from([("class".to_string(), "styled".to_string())]),
+        );
+    }
+
+    let input = "Intro\n\n- [Top](#top)\n- Two\n\n| N |\n|---|\n| 1 |";
+    assert_eq!(
+        render_with_options(input, Options::ENABLE_TABLES, &config),
+        "<p class=\"lead styled\">Intro</p>\
+         <ul><li class=\"first styled\"><a href=\"#top\" class=\"anchor styled\">Top</a></li>\
+         <li class=\"last styled\">Two</li></ul>\
+         <table><thead><tr><th class=\"num styled\">N</th></tr></thead>\
+         <tbody><tr><td class=\"num styled\">1</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn test_strict_nesting() {
+    use pulldown_cmark::{Event, Tag, TagEnd};
+
+    let events = || {
+        vec![
+            Event::Start(Tag::Paragraph),
+            Event::Start(Tag::Emphasis),
+            Event::Start(Tag::Strong),
+            Event::Text("text".into()),
+            Event::End(TagEnd::Emphasis),
+            Event::End(TagEnd::Strong),
+            Event::End(TagEnd::Paragraph),
+        ]
+    };
+
+    let mut config = HtmlConfig::default();
+    let mut output = String::new();
+    push_html(&mut output, events().into_iter(), &config).unwrap();
+
+    config.html.strict_nesting = true;
+    let mut output = String::new();
+    let result = push_html(&mut output, events().into_iter(), &config);
+    assert!(matches!(result, Err(HtmlError::Render(_))));
+
+    // Well-formed documents render as usual
+    let mut output = String::new();
+    let markdown = "*a **b** [c]()* ![d *e*](f.png)\n\n| x |\n|---|\n| y |";
+    push_html(&mut output, Parser::new(markdown), &config).unwrap();
+    assert!(output.contains("<em>a <strong>b</strong>"));
+}
+
+#[test]
+fn test_image_srcset_resolver() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.images.default_sizes = Some("(max-width: 600px) 100vw".to_string());
+    config.hooks.srcset_resolver = Some(Arc::new(|src: &str| {
+        let stem = src.strip_suffix(".png")?;
+        Some(format!("{stem}.png 1x, {stem}@2x.png 2x"))
+    }));
+
+    assert_eq!(
+        render_with_config("![Cat](cat.png) ![Logo](logo.svg)", &config),
+        "<p><img src=\"cat.png\" alt=\"Cat\" srcset=\"cat.png 1x, cat@2x.png 2x\" \
+         sizes=\"(max-width: 600px) 100vw\"> <img src=\"logo.svg\" alt=\"Logo\"></p>"
+    );
+}
+
+#[test]
+fn test_bare_pre_languages() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.code_blocks.bare_pre_languages = vec!["ascii".to_string()];
+
+    assert_eq!(
+        render_with_config("```ascii\n+--+\n|  |\n```\n```rust\nlet x;\n```", &config),
+        "<pre>+--+\n|  |\n</pre><pre><code class=\"language-rust\">let x;\n</code></pre>"
+    );
+}
+
+#[test]
+fn test_hr_render_as() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    assert_eq!(
+        render_with_config("a\n\n---\n\nb", &config),
+        "<p>a</p><hr><p>b</p>"
+    );
+
+    config.elements.hr.render_as = HrRender::Div("divider".to_string());
+    assert_eq!(
+        render_with_config("a\n\n---\n\nb", &config),
+        "<p>a</p><div class=\"divider\"></div><p>b</p>"
+    );
+}
+
+#[test]
+fn test_always_emit_start() {
+    let mut config = HtmlConfig::default();
+    assert_html_eq!(
+        render_with_config("1. One\n2. Two", &config),
+        "<ol><li>One</li><li>Two</li></ol>"
+    );
+
+    config.elements.lists.always_emit_start = true;
+    assert_html_eq!(
+        render_with_config("1. One\n2. Two", &config),
+        "<ol start=\"1\"><li>One</li><li>Two</li></ol>"
+    );
+}
+
+#[test]
+fn test_list_depth_attribute() {
+    let mut config = HtmlConfig::default();
+    config.elements.lists.depth_attribute = true;
+    assert_html_eq!(
+        render_with_config("- One\n  1. Nested\n- Two", &config),
+        "<ul data-depth=\"1\"><li>One<ol data-depth=\"2\"><li>Nested</li></ol></li><li>Two</li></ul>"
+    );
+}
+
+#[test]
+fn test_element_hooks() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.headings.add_ids = false;
+    config.hooks.element_hooks.insert(
+        "h2".to_string(),
+        Arc::new(|| "<hr class=\"divider\">".to_string()),
+    );
+
+    assert_eq!(
+        render_with_config("# Title\n\n## Part\n\nText", &config),
+        "<h1>Title</h1><h2>Part</h2><hr class=\"divider\"><p>Text</p>"
+    );
+}
+
+#[test]
+fn test_code_block_info_attributes() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.code_blocks.info_attributes = true;
+
+    let markdown = "```rust title='\"><script>alert(1)</script>' on\"x=1 hl=2\nlet x;\n```";
+    assert_eq!(
+        render_with_config(markdown, &config),
+        "<pre data-title=\"&quot;&gt;&lt;script&gt;alert(1)&lt;/script&gt;\" data-hl=\"2\">\
+         <code class=\"language-rust\">let x;\n</code></pre>"
+    );
+}
+
+#[test]
+fn test_render_with_frontmatter() {
+    let config = HtmlConfig::default();
+    let markdown = "---\ntitle: Hello\ntags: [a, b]\n---\n# Hello\n\nBody";
+
+    let (front_matter, html) = render_with_frontmatter(markdown, &config).unwrap();
+    let front_matter = front_matter.unwrap();
+    assert_eq!(
+        front_matter.kind,
+        pulldown_cmark::MetadataBlockKind::YamlStyle
+    );
+    assert_eq!(front_matter.raw, "title: Hello\ntags: [a, b]\n");
+    assert_html_eq!(html, "<h1 id=\"heading-1\">Hello</h1><p>Body</p>");
+
+    let (front_matter, html) = render_with_frontmatter("Body", &config).unwrap();
+    assert!(front_matter.is_none());
+    assert_html_eq!(html, "<p>Body</p>");
+}
+
+#[test]
+fn test_empty_id_fallback() {
+    let mut config = HtmlConfig::default();
+    config.elements.headings.id_from_content = true;
+    config.elements.headings.id_prefix = String::new();
+    config.elements.headings.on_duplicate_id = DupPolicy::Suffix;
+
+    assert_html_eq!(
+        render_with_config("## !!!\n\n## ???\n\n## Real", &config),
+        "<h2 id=\"section\">!!!</h2><h2 id=\"section-1\">???</h2><h2 id=\"real\">Real</h2>"
+    );
+}
+
+#[test]
+fn test_task_list_wrap_label() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.task_lists.wrap_label = true;
+
+    assert_eq!(
+        render_with_options(
+            "- [x] Done\n- Plain\n- [ ] Todo\n  - [ ] Nested",
+            Options::ENABLE_TASKLISTS,
+            &config
+        ),
+        "<ul><li><label><input type=\"checkbox\" disabled checked>Done</label></li>\
+         <li>Plain</li>\
+         <li><label><input type=\"checkbox\" disabled>Todo</label>\
+         <ul><li><label><input type=\"checkbox\" disabled>Nested</label></li></ul>\
+         </li></ul>"
+    );
+
+    // In a loose list the label ends with the item's first paragraph
+    assert_eq!(
+        render_with_options(
+            "- [x] Done\n\n  More\n\n- [ ] Todo\n\n  > Quote",
+            Options::ENABLE_TASKLISTS,
+            &config
+        ),
+        "<ul><li><p><label><input type=\"checkbox\" disabled checked>Done</label></p><p>More</p></li>\
+         <li><p><label><input type=\"checkbox\" disabled>Todo</label></p>\
+         <blockquote><p>Quote</p></blockquote></li></ul>"
+    );
+}
+
+#[test]
+fn test_heading_auto_number() {
+    let mut config = HtmlConfig::default();
+    config.elements.headings.add_ids = false;
+    config.elements.headings.auto_number = true;
+
+    let markdown = "# Intro\n## Goals\n## Scope\n### Limits\n# Usage\n### Deep\n## Setup";
+    assert_html_eq!(
+        render_with_config(markdown, &config),
+        "<h1>1. Intro</h1><h2>1.1. Goals</h2><h2>1.2. Scope</h2><h3>1.2.1. Limits</h3>\
+         <h1>2. Usage</h1><h3>2.0.1. Deep</h3><h2>2.1. Setup</h2>"
+    );
+}
+
+#[test]
+fn test_code_block_filename_header() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.code_blocks.filename_header = true;
+
+    assert_eq!(
+        render_with_config(
+            "```rust title=\"src/<main>.rs\"\nfn main() {}\n```",
+            &config
+        ),
+        "<div class=\"code-filename\">src/&lt;main&gt;.rs</div>\
+         <pre><code class=\"language-rust\">fn main() {}\n</code></pre>"
+    );
+    assert_eq!(
+        render_with_config("```sh filename=run.sh\nls\n```\n```sh\nls\n```", &config),
+        "<div class=\"code-filename\">run.sh</div><pre><code class=\"language-sh\">ls\n</code></pre>\
+         <pre><code class=\"language-sh\">ls\n</code></pre>"
+    );
+}
+
+#[test]
+fn test_code_span_preserve_edge_space() {
+    let render = |config: &HtmlConfig| {
+        let input = "Use `` `tick` `` and ` x ` or `y`";
+        let mut output = String::new();
+        let handler = DefaultHtmlWriter::new(&mut output, config.clone());
+        let mut renderer = HtmlRenderer::new(handler);
+        renderer
+            .run_with_offsets(input, Parser::new(input).into_offset_iter())
+            .unwrap();
+        output
+    };
+
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    assert_eq!(
+        render(&config),
+        "<p>Use <code>`tick`</code> and <code>x</code> or <code>y</code></p>"
+    );
+
+    config.elements.code_spans.preserve_edge_space = true;
+    assert_eq!(
+        render(&config),
+        "<p>Use <code> `tick` </code> and <code> x </code> or <code>y</code></p>"
+    );
+}
+
+#[test]
+fn test_heading_mark_syntax() {
+    let input =
+        "Title\n=====\n\n# Title\n\nPart\n----\n\n## Part ##\n\n#tag\n===\n\n\\# Escaped\n---";
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.headings.add_ids = false;
+    config.elements.headings.mark_syntax = true;
+
+    let mut output = String::new();
+    let handler = DefaultHtmlWriter::new(&mut output, config);
+    let mut renderer = HtmlRenderer::new(handler);
+    renderer
+        .run_with_offsets(input, Parser::new(input).into_offset_iter())
+        .unwrap();
+    assert_eq!(
+        output,
+        "<h1 data-heading-syntax=\"setext\">Title</h1><h1 data-heading-syntax=\"atx\">Title</h1>\
+         <h2 data-heading-syntax=\"setext\">Part</h2><h2 data-heading-syntax=\"atx\">Part</h2>\
+         <h1 data-heading-syntax=\"setext\">#tag</h1><h2 data-heading-syntax=\"setext\"># Escaped</h2>"
+    );
+}
+
+#[test]
+fn test_detect_dates() {
+    let mut config = HtmlConfig::default();
+    config.html.detect_dates = true;
+
+    assert_html_eq!(
+        render_with_config(
+            "Released on 2024-01-15, see `2024-01-15` and v2024-01-16.",
+            &config
+        ),
+        "<p>Released on <time datetime=\"2024-01-15\">2024-01-15</time>, \
+         see <code>2024-01-15</code> and v2024-01-16.</p>"
+    );
+}
+
+#[test]
+fn test_blockquote_tag() {
+    let mut config = HtmlConfig::default();
+    assert_html_eq!(
+        render_with_config("> Quote", &config),
+        "<blockquote><p>Quote</p></blockquote>"
+    );
+
+    config.elements.blockquotes.tag = BlockquoteTag::Aside;
+    assert_html_eq!(
+        render_with_config("> Quote\n>\n> > Nested", &config),
+        "<aside><p>Quote</p><aside><p>Nested</p></aside></aside>"
+    );
+}
+
+#[test]
+fn test_code_block_line_numbers() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.code_blocks.line_numbers = true;
+
+    assert_eq!(
+        render_with_config("```rust startline=42\nlet a;\nlet b;\n```", &config),
+        "<pre><code class=\"language-rust\">\
+         <span class=\"line-number\">42</span>let a;\n\
+         <span class=\"line-number\">43</span>let b;\n</code></pre>"
+    );
+    assert!(render_with_config("```\nx\n```", &config)
+        .contains("<code><span class=\"line-number\">1</span>x\n</code>"));
+
+    // A start line near usize::MAX must not overflow when counting lines
+    let max = usize::MAX;
+    assert!(
+        render_with_config(&format!("```x startline={max}\na\nb\n```"), &config).contains(
+            &format!("<span class=\"line-number\">{max}</span>a\n<span class=\"line-number\">{max}</span>b\n")
+        )
+    );
+}
+
+#[test]
+fn test_global_block_attributes() {
+    let mut config = HtmlConfig::default();
+    config.elements.headings.add_ids = false;
+    config
+        .html
+        .global_block_attributes
+        .insert("data-md".to_string(), "true".to_string());
+
+    assert_html_eq!(
+        render_with_config("# Title\n\n*Some* text\n\n- item\n\n> quote", &config),
+        "<h1 data-md=\"true\">Title</h1>\
+         <p data-md=\"true\"><em>Some</em> text</p>\
+         <ul data-md=\"true\"><li data-md=\"true\">item</li></ul>\
+         <blockquote data-md=\"true\"><p data-md=\"true\">quote</p></blockquote>"
+    );
+}
+
+#[test]
+fn test_attribute_sources_merge_by_key() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config
+        .html
+        .global_block_attributes
+        .insert("data-x".to_string(), "global".to_string());
+    config.attributes.element_attributes.insert(
+        "p".to_string(),
+        HashMap::from([("data-x".to_string(), "element".to_string())]),
+    );
+
+    assert_eq!(
+        render_with_config("Text", &config),
+        "<p data-x=\"element\">Text</p>"
+    );
+
+    config.hooks.attribute_provider = Some(Arc::new(|tag: &str, _: &ElementInfo| {
+        if tag == "p" {
+            vec![("data-x".to_string(), "provider".to_string())]
+        } else {
+            Vec::new()
+        }
+    }));
+    assert_eq!(
+        render_with_config("Text", &config),
+        "<p data-x=\"provider\">Text</p>"
+    );
+}
+
+#[test]
+fn test_footnotes_collect_at_end() {
+    let mut config = HtmlConfig::default();
+    config.elements.footnotes.collect_at_end = true;
+
+    let markdown = "Text[^1].\n\n[^1]: The note.\n\nMore text.";
+    let html = render_with_options(markdown, Options::ENABLE_FOOTNOTES, &config);
+    let note = html.find("The note.").unwrap();
+    assert!(html.find("More text.").unwrap() < note);
+    assert!(html.trim_end().ends_with("</div>"));
+
+    config.elements.footnotes.collect_at_end = false;
+    let html = render_with_options(markdown, Options::ENABLE_FOOTNOTES, &config);
+    assert!(html.find("The note.").unwrap() < html.find("More text.").unwrap());
+}
+
+#[test]
+fn test_image_fallback_src() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+
+    config.elements.images.fallback_src = Some("/img/missing.png?a=1&b='x'".to_string());
+    assert_eq!(
+        render_with_config("![Cat](cat.png)", &config),
+        "<p><img src=\"cat.png\" alt=\"Cat\" \
+         onerror=\"this.onerror=null;this.src=&#39;/img/missing.png?a=1&amp;b=%27x%27&#39;\"></p>"
+    );
+
+    config.elements.images.fallback_src = Some("javascript:alert(1)".to_string());
+    assert_eq!(
+        render_with_config("![Cat](cat.png)", &config),
+        "<p><img src=\"cat.png\" alt=\"Cat\"></p>"
+    );
+}
+
+#[test]
+fn test_run_with_separator() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+
+    let mut output = String::new();
+    let handler = DefaultHtmlWriter::new(&mut output, config);
+    let mut renderer = HtmlRenderer::new(handler);
+    renderer.run_with_separator(Parser::new("One")).unwrap();
+    renderer.run_with_separator(Parser::new("Two")).unwrap();
+
+    assert_eq!(output, "<p>One</p><hr class=\"doc-sep\"><p>Two</p>");
+}
+
+#[test]
+fn test_escape_raw_html_keeps_entities() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.html.escape_html = true;
+
+    assert_eq!(
+        render_with_config("<div title=\"a &amp; b\">Tom &amp; *Jerry*</div>", &config),
+        "&lt;div title=&quot;a &amp; b&quot;&gt;Tom &amp; *Jerry*&lt;/div&gt;"
+    );
+    assert_eq!(
+        render_with_config("Inline <b>&lt;bold&gt;</b> & more", &config),
+        "<p>Inline &lt;b&gt;&lt;bold&gt;&lt;/b&gt; &amp; more</p>"
+    );
+
+    config.html.escape_html = false;
+    assert_eq!(
+        render_with_config("<div>Tom &amp; *Jerry*</div>", &config),
+        "<div>Tom &amp; *Jerry*</div>"
+    );
+}
+
+#[test]
+fn test_image_only_paragraph_class() {
+    let mut config = HtmlConfig::default();
+    config.elements.paragraphs.image_only_class = Some("gallery".to_string());
+
+    assert_html_eq!(
+        render_with_config(
+            "![A](a.png)\n\nSee ![B](b.png) here\n\n![C *c*](c.png) ![D](d.png)",
+            &config
+        ),
+        "<p class=\"gallery\"><img src=\"a.png\" alt=\"A\"></p>\
+         <p>See <img src=\"b.png\" alt=\"B\"> here</p>\
+         <p><img src=\"c.png\" alt=\"C c\"> <img src=\"d.png\" alt=\"D\"></p>"
+    );
+}
+
+#[test]
+fn test_break_with_newline() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    let input = "One  \nTwo\nThree";
+
+    assert_eq!(
+        render_with_config(input, &config),
+        "<p>One<br>Two<br>Three</p>"
+    );
+
+    config.html.break_with_newline = true;
+    assert_eq!(
+        render_with_config(input, &config),
+        "<p>One<br>\nTwo<br>\nThree</p>"
+    );
+
+    config.html.break_on_newline = false;
+    assert_eq!(
+        render_with_config(input, &config),
+        "<p>One<br>\nTwo\nThree</p>"
+    );
+}
+
+#[test]
+fn test_emoji_shortcodes() {
+    let mut config = HtmlConfig::default();
+    let input = "Ship it :rocket: :unknown: `:rocket:`";
+
+    assert_html_eq!(
+        render_with_config(input, &config),
+        "<p>Ship it :rocket: :unknown: <code>:rocket:</code></p>"
+    );
+
+    config.html.emoji = EmojiMode::Unicode;
+    assert_html_eq!(
+        render_with_config(input, &config),
+        "<p>Ship it 🚀 :unknown: <code>:rocket:</code></p>"
+    );
+
+    config.html.emoji = EmojiMode::Image("https://cdn.example.com/{name}.png".to_string());
+    assert_html_eq!(
+        render_with_config(input, &config),
+        "<p>Ship it <img class=\"emoji\" src=\"https://cdn.example.com/rocket.png\" alt=\":rocket:\"> \
+         :unknown: <code>:rocket:</code></p>"
+    );
+}
+
+#[test]
+fn test_hr_in_lists() {
+    let mut config = HtmlConfig::default();
+    let between = "- a\n\n***\n\n- b";
+    let inside = "- a\n\n  ***\n- b";
+
+    // A rule between items ends the list, and one inside an item stays in it
+    assert_html_eq!(
+        render_with_config(between, &config),
+        "<ul><li>a</li></ul><hr><ul><li>b</li></ul>"
+    );
+    assert_html_eq!(
+        render_with_config(inside, &config),
+        "<ul><li><p>a</p><hr></li><li><p>b</p></li></ul>"
+    );
+
+    config.elements.hr.suppress_in_lists = true;
+    assert_html_eq!(
+        render_with_config(between, &config),
+        "<ul><li>a</li></ul><hr><ul><li>b</li></ul>"
+    );
+    assert_html_eq!(
+        render_with_config(inside, &config),
+        "<ul><li><p>a</p></li><li><p>b</p></li></ul>"
+    );
+}
+
+#[test]
+fn test_trim_trailing_whitespace() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.html.break_on_newline = false;
+    let input = "one&#32;\ntwo \\\nthree\n\n```\ncode  \n```";
+
+    assert_eq!(
+        render_with_config(input, &config),
+        "<p>one \ntwo <br>three</p><pre><code>code  \n</code></pre>"
+    );
+
+    config.html.trim_trailing_whitespace = true;
+    // Whitespace before the hard break and inside code is kept
+    assert_eq!(
+        render_with_config(input, &config),
+        "<p>one\ntwo <br>three</p><pre><code>code  \n</code></pre>"
+    );
+}
+
+#[test]
+fn test_id_generator() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    let counter = AtomicUsize::new(0);
+    config.hooks.id_generator = Some(Arc::new(move |tag: &str| {
+        (tag == "p").then(|| format!("p-{}", counter.fetch_add(1, Ordering::Relaxed) + 1))
+    }));
+
+    // Generated IDs are kept unique against heading IDs
+    assert_eq!(
+        render_with_options(
+            "# Title {#p-2}\n\nOne\n\nTwo\n\n- item",
+            Options::ENABLE_HEADING_ATTRIBUTES,
+            &config
+        ),
+        "<h1 id=\"p-2\">Title</h1><p id=\"p-1\">One</p><p id=\"p-2-1\">Two</p>\
+         <ul><li>item</li></ul>"
+    );
+}
+
+#[test]
+fn test_max_inline_nesting() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.html.max_inline_nesting = Some(2);
+
+    assert_eq!(
+        render_with_config("*a **b *c **d** c* b** a*", &config),
+        "<p><em>a <strong>b *c **d** c* b</strong> a</em></p>"
+    );
+
+    // Deeply nested input degrades to literal markers without blowing up
+    let deep = format!("{}x{}", "*".repeat(10000), "*".repeat(10000));
+    let html = render_with_config(&deep, &config);
+    assert_eq!(
+        html.matches("<em>").count() + html.matches("<strong>").count(),
+        2
+    );
+    assert!(html.contains('x'));
+
+    // Unbalanced end tags in hand-built event streams are tolerated
+    let events = vec![
+        Event::Start(Tag::Paragraph),
+        Event::Text("a".into()),
+        Event::End(TagEnd::Emphasis),
+        Event::End(TagEnd::Strong),
+        Event::End(TagEnd::Paragraph),
+    ];
+    let mut output = String::new();
+    push_html(&mut output, events.into_iter(), &config).unwrap();
+    assert_eq!(output, "<p>a</em></strong></p>");
+}
+
+#[test]
+fn test_table_column_classes() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.tables.column_classes = vec!["name".to_string(), "num".to_string()];
+
+    let input = "| a | b | c |\n|---|---|---|\n| 1 | 2 | 3 |\n| 4 |";
+    assert_eq!(
+        render_with_options(input, Options::ENABLE_TABLES, &config),
+        "<table><thead><tr><th class=\"name\">a</th><th class=\"num\">b</th><th>c</th></tr>\
+         </thead><tbody><tr><td class=\"name\">1</td><td class=\"num\">2</td><td>3</td></tr>\
+         <tr><td class=\"name\">4</td><td class=\"num\"></td><td></td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn test_heading_level_attribute() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.headings.add_ids = false;
+    config.elements.headings.emit_level_attr = true;
+
+    assert_eq!(
+        render_with_config("# One\n\n### Three", &config),
+        "<h1 data-level=\"1\">One</h1><h3 data-level=\"3\">Three</h3>"
+    );
+}
+
+#[test]
+fn test_blockquote_unwrap_single_paragraph() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.blockquotes.unwrap_single_paragraph = true;
+
+    assert_eq!(
+        render_with_config("> quoted *text*", &config),
+        "<blockquote>quoted <em>text</em></blockquote>"
+    );
+    assert_eq!(
+        render_with_config("> one\n>\n> two", &config),
+        "<blockquote><p>one</p><p>two</p></blockquote>"
+    );
+    assert_eq!(
+        render_with_config("> one\n> - item", &config),
+        "<blockquote><p>one</p><ul><li>item</li></ul></blockquote>"
+    );
+}
+
+#[test]
+fn test_image_src_resolver() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.hooks.image_src_resolver = Some(Arc::new(|src: &str| {
+        format!("https://cdn.example.com/{}", src.trim_start_matches('/'))
+    }));
+
+    assert_eq!(
+        render_with_config("![Logo][logo]\n\n[logo]: img/logo.png", &config),
+        "<p><img src=\"https://cdn.example.com/img/logo.png\" alt=\"Logo\"></p>"
+    );
+    // Links are left alone
+    assert_eq!(
+        render_with_config("[Logo](img/logo.png)", &config),
+        "<p><a href=\"img/logo.png\">Logo</a></p>"
+    );
+}
+
+#[test]
+fn test_code_block_drop_empty() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    let input = "```rust\n```\n\n```\n  \n```\n\n```\nkept\n```";
+
+    assert_eq!(
+        render_with_config(input, &config),
+        "<pre><code class=\"language-rust\"></code></pre><pre><code>  \n</code></pre>\
+         <pre><code>kept\n</code></pre>"
+    );
+
+    config.elements.code_blocks.drop_empty = true;
+    assert_eq!(
+        render_with_config(input, &config),
+        "<pre><code>kept\n</code></pre>"
+    );
+}
+
+#[test]
+fn test_image_default_dimensions() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+
+    // Titles are left alone unless dimensions are configured
+    assert_eq!(
+        render_with_config("![s](s.png \"Screen =800x600\")", &config),
+        "<p><img src=\"s.png\" alt=\"s\" title=\"Screen =800x600\"></p>"
+    );
+
+    config.elements.images.default_width = Some(800);
+    config.elements.images.default_height = Some(600);
+
+    assert_eq!(
+        render_with_config("![a](a.png)", &config),
+        "<p><img src=\"a.png\" alt=\"a\" width=\"800\" height=\"600\"></p>"
+    );
+    // Dimensions in the title take precedence
+    assert_eq!(
+        render_with_config("![b](b.png \"Chart =320x200\")", &config),
+        "<p><img src=\"b.png\" alt=\"b\" width=\"320\" height=\"200\" title=\"Chart\"></p>"
+    );
+    // As do configured attributes
+    config.hooks.attribute_provider = Some(Arc::new(|tag: &str, _: &ElementInfo| {
+        if tag == "img" {
+            vec![("width".to_string(), "100%".to_string())]
+        } else {
+            Vec::new()
+        }
+    }));
+    assert_eq!(
+        render_with_config("![c](c.png)", &config),
+        "<p><img src=\"c.png\" alt=\"c\" height=\"600\" width=\"100%\"></p>"
+    );
+}
+
+#[test]
+fn test_hr_number_sections() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.hr.number_sections = true;
+
+    assert_eq!(
+        render_with_config("One\n\n---\n\nTwo\n\n***\n\nThree\n\n___", &config),
+        "<p>One</p><hr data-section=\"1\"><p>Two</p><hr data-section=\"2\">\
+         <p>Three</p><hr data-section=\"3\">"
+    );
+}
+
+#[test]
+fn test_heading_duplicate_id_policy() {
+    let input = "## Setup\n\n## Setup";
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.headings.id_prefix = String::new();
+    config.elements.headings.id_from_content = true;
+
+    assert_eq!(
+        render_with_config(input, &config),
+        "<h2 id=\"setup\">Setup</h2><h2 id=\"setup\">Setup</h2>"
+    );
+
+    config.elements.headings.on_duplicate_id = DupPolicy::Suffix;
+    assert_eq!(
+        render_with_config(input, &config),
+        "<h2 id=\"setup\">Setup</h2><h2 id=\"setup-1\">Setup</h2>"
+    );
+
+    config.elements.headings.on_duplicate_id = DupPolicy::Skip;
+    assert_eq!(
+        render_with_config(input, &config),
+        "<h2 id=\"setup\">Setup</h2><h2>Setup</h2>"
+    );
+
+    config.elements.headings.on_duplicate_id = DupPolicy::Error;
+    let mut output = String::new();
+    let result = push_html(&mut output, Parser::new(input), &config);
+    assert!(matches!(result, Err(HtmlError::Render(message)) if message.contains("'setup'")));
+}
+
+#[test]
+fn test_code_block_language_families() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    let families = &mut config.elements.code_blocks.language_families;
+    for lang in ["c", "cpp", "rust"] {
+        families.insert(lang.to_string(), "systems".to_string());
+    }
+    config
+        .elements
+        .code_blocks
+        .language_aliases
+        .insert("rs".to_string(), "rust".to_string());
+
+    assert_eq!(
+        render_with_config("```rs\nfn main() {}\n```\n```python\npass\n```", &config),
+        "<pre><code class=\"language-rust\" data-lang-family=\"systems\">fn main() {}\n</code></pre>\
+         <pre><code class=\"language-python\">pass\n</code></pre>"
+    );
+}
+
+#[test]
+fn test_code_span_insert_wbr() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.code_spans.insert_wbr = true;
+
+    assert_eq!(
+        render_with_config(
+            "`std::io::Write` in `pkg/sub-dir/main.rs.`\n\n```\na.b\n```",
+            &config
+        ),
+        "<p><code>std::<wbr>io::<wbr>Write</code> in \
+         <code>pkg/<wbr>sub-<wbr>dir/<wbr>main.<wbr>rs.</code></p><pre><code>a.b\n</code></pre>"
+    );
+}
+
+#[test]
+fn test_first_image_class() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.images.first_image_class = Some("cover".to_string());
+
+    assert_eq!(
+        render_with_config("Intro\n\n![a](a.png)\n\n![b](b.png)", &config),
+        "<p>Intro</p><p><img src=\"a.png\" alt=\"a\" class=\"cover\"></p>\
+         <p><img src=\"b.png\" alt=\"b\"></p>"
+    );
+}
+
+#[test]
+fn test_table_as_definition_list() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.tables.as_definition_list = true;
+
+    let input = "| Name | Role |\n|------|------|\n| Ada | *Engineer* |\n| Grace |";
+    assert_eq!(
+        render_with_options(input, Options::ENABLE_TABLES, &config),
+        "<dl><dt>Name</dt><dd>Ada</dd><dt>Role</dt><dd><em>Engineer</em></dd>\
+         <dt>Name</dt><dd>Grace</dd><dt>Role</dt><dd></dd></dl>"
+    );
+}
+
+#[test]
+fn test_encode_non_ascii() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.html.escape_html = true;
+    config.html.encode_non_ascii = true;
+    let input = "Café & crème 😀 `ü`\n\n```\nnaïve\n```";
+
+    assert_eq!(
+        render_with_config(input, &config),
+        "<p>Caf&#233; &amp; cr&#232;me &#128512; <code>&#252;</code></p>\
+         <pre><code>naïve\n</code></pre>"
+    );
+
+    config.html.encode_non_ascii_in_code = true;
+    assert!(render_with_config(input, &config).contains("<pre><code>na&#239;ve\n"));
+}
+
+#[test]
+fn test_amp_mode_images() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.html.amp_mode = true;
+    config.elements.images.default_width = Some(800);
+    config.elements.images.default_height = Some(600);
+    config.elements.images.fallback_src = Some("https://example.com/missing.png".to_string());
+    config.hooks.attribute_provider = Some(Arc::new(|tag: &str, _: &ElementInfo| {
+        if tag == "img" {
+            vec![
+                ("style".to_string(), "border: 0".to_string()),
+                ("loading".to_string(), "lazy".to_string()),
+            ]
+        } else {
+            Vec::new()
+        }
+    }));
+
+    assert_eq!(
+        render_with_config(
+            "![Chart](chart.png \"=320x200\")\n\n![Logo](logo.png)",
+            &config
+        ),
+        "<p><amp-img src=\"chart.png\" alt=\"Chart\" width=\"320\" height=\"200\" \
+         loading=\"lazy\"></amp-img></p><p><amp-img src=\"logo.png\" alt=\"Logo\" \
+         width=\"800\" height=\"600\" loading=\"lazy\"></amp-img></p>"
+    );
+
+    // Images without dimensions cannot be rendered
+    config.elements.images.default_height = None;
+    let mut output = String::new();
+    let result = push_html(&mut output, Parser::new("![Logo](logo.png)"), &config);
+    assert!(matches!(result, Err(HtmlError::Render(_))));
+}
+
+#[test]
+fn test_wrap_loose_text() {
+    use pulldown_cmark::{CowStr, Event, Tag, TagEnd};
+
+    let events = || {
+        vec![
+            Event::Text(CowStr::from("Loose ")),
+            Event::Start(Tag::Emphasis),
+            Event::Text(CowStr::from("text")),
+            Event::End(TagEnd::Emphasis),
+            Event::Start(Tag::Paragraph),
+            Event::Text(CowStr::from("Wrapped")),
+            Event::End(TagEnd::Paragraph),
+            Event::Text(CowStr::from("More")),
+        ]
+    };
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+
+    let mut output = String::new();
+    push_html(&mut output, events().into_iter(), &config).unwrap();
+    assert_eq!(output, "Loose <em>text</em><p>Wrapped</p>More");
+
+    config.html.wrap_loose_text = true;
+    let mut output = String::new();
+    push_html(&mut output, events().into_iter(), &config).unwrap();
+    assert_eq!(
+        output,
+        "<p>Loose <em>text</em></p><p>Wrapped</p><p>More</p>"
+    );
+
+    // HTML blocks and front matter are not loose inline content
+    assert_eq!(
+        render_with_config("<div>\nx\n</div>\n\ntext", &config),
+        "<div>\nx\n</div>\n<p>text</p>"
+    );
+    assert_eq!(
+        render_with_config("<!-- more -->\n\ntext", &config),
+        "<!-- more -->\n<p>text</p>"
+    );
+    let front_matter = "---\ntitle: x\n---\n\nBody";
+    let render = |config: &HtmlConfig| {
+        String::from_utf8(render_bytes(front_matter, config).unwrap()).unwrap()
+    };
+    assert_eq!(render(&config), "<p>Body</p>");
+    config.html.unknown_event_policy = UnknownPolicy::Comment;
+    assert_eq!(render(&config), "<!-- title: x\n --><p>Body</p>");
+}
+
+#[test]
+fn test_link_download_extensions() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.links.download_extensions = vec!["pdf".to_string(), ".zip".to_string()];
+
+    assert_eq!(
+        render_with_config(
+            "[Paper](/files/paper.PDF?v=2) [Source](src.zip) [Page](/docs/page.html)",
+            &config
+        ),
+        "<p><a href=\"/files/paper.PDF?v=2\" download>Paper</a> <a href=\"src.zip\" download>Source</a> \
+         <a href=\"/docs/page.html\">Page</a></p>"
+    );
+}
+
+#[test]
+fn test_reference_index() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.elements.links.emit_reference_index = true;
+
+    let input = "See [Knuth][taocp], [Dijkstra] and [Knuth again][taocp] or [inline](/x).\n\n\
+                 [taocp]: /taocp \"TAOCP\"\n\
+                 [Dijkstra]: /ewd";
+    assert_eq!(
+        render_with_config(input, &config),
+        "<p>See <a href=\"/taocp\" title=\"TAOCP\">Knuth</a>, \
+         <a href=\"/ewd\">Dijkstra</a> and \
+         <a href=\"/taocp\" title=\"TAOCP\">Knuth again</a> or <a href=\"/x\">inline</a>.</p>\
+         <ol class=\"reference-index\">\
+         <li><a href=\"/taocp\" title=\"TAOCP\">/taocp</a></li>\
+         <li><a href=\"/ewd\">/ewd</a></li></ol>"
+    );
+}
+
+#[test]
+fn test_heading_renderer_hook() {
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    config.hooks.heading_renderer = Some(Arc::new(|level: u8, id: &str, text: &str| {
+        format!(
+            "<div class=\"heading\"><h{0} id=\"{1}\">{2}</h{0}><a href=\"/edit#{1}\">edit</a></div>",
+            level, id, text
+        )
+    }));
+
+    assert_eq!(
+        render_with_config("# Getting *started*\n\nText\n\n## Setup", &config),
+        "<div class=\"heading\"><h1 id=\"heading-1\">Getting started</h1>\
+         <a href=\"/edit#heading-1\">edit</a></div>\
+         <p>Text</p>\
+         <div class=\"heading\"><h2 id=\"heading-2\">Setup</h2>\
+         <a href=\"/edit#heading-2\">edit</a></div>"
+    );
+}
+
+#[test]
+fn test_code_block_language_aliases() {
+    let mut config = HtmlConfig::default();
+    config
+        .elements
+        .code_blocks
+        .language_aliases
+        .insert("js".to_string(), "javascript".to_string());
+
+    // Aliased languages are replaced by their canonical name
+    assert_html_eq!(
+        render_with_config("```js\nlet x = 1;\n```", &config),
+        "<pre><code class=\"language-javascript\">let x = 1;</code></pre>"
+    );
+
+    // Languages without an alias are left untouched
+    assert_html_eq!(
+        render_with_config("```rust\nlet x = 1;\n```", &config),
+        "<pre><code class=\"language-rust\">let x = 1;</code></pre>"
+    );
+
+    // The language as written can be kept alongside the canonical one
+    config.elements.code_blocks.keep_original_language = true;
+    assert_html_eq!(
+        render_with_config("```js\nlet x = 1;\n```", &config),
+        "<pre><code class=\"language-javascript\" data-lang-original=\"js\">let x = 1;</code></pre>"
+    );
+    assert_html_eq!(
+        render_with_config("```rust\nlet x = 1;\n```", &config),
+        "<pre><code class=\"language-rust\">let x = 1;</code></pre>"
+    );
+}
+
+#[test]
+fn test_code_block_trim_blank_lines() {
+    let input = "```\n\n  \nfirst\n\nsecond\n\n\n```";
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+
+    config.elements.code_blocks.trim_blank_lines = false;
+    assert_eq!(
+        render_with_config(input, &config),
+        "<pre><code>\n  \nfirst\n\nsecond\n\n\n</code></pre>"
+    );
+
+    // Internal blank lines are kept while the padding is removed
+    config.elements.code_blocks.trim_blank_lines = true;
+    assert_eq!(
+        render_with_config(input, &config),
+        "<pre><code>first\n\nsecond\n</code></pre>"
+    );
+}
+
+#[test]
+fn test_custom_attributes() {
+    let mut config = HtmlConfig::default();
+    let mut element_attributes = HashMap::new();
+
+    // Add attributes for paragraphs
+    let mut p_attrs = HashMap::new();
+    p_attrs.insert("class".to_string(), "content".to_string());
+    element_attributes.insert("p".to_string(), p_attrs);
+
+    // Add attributes for code blocks
+    let mut pre_attrs = HashMap::new();
+    pre_attrs.insert("data-type".to_string(), "code".to_string());
+    element_attributes.insert("pre".to_string(), pre_attrs);
+
+    config.attributes.element_attributes = element_attributes;
+
+    assert_html_eq!(
+        render_with_config("Regular paragraph\n\n```\nCode block\n```", &config),
+        "<p class=\"content\">Regular paragraph</p>\
+             <pre data-type=\"code\"><code>Code block</code></pre>"
+    );
+}
+
+#[test]
+fn test_horizontal_rule_attributes() {
+    let mut config = HtmlConfig::default();
+    let mut hr_attrs = HashMap::new();
+    hr_attrs.insert("class".to_string(), "divider".to_string());
+    config
+        .attributes
+        .element_attributes
+        .insert("hr".to_string(), hr_attrs);
+
+    assert_html_eq!(
+        render_with_config("Above\n\n---\n\nBelow", &config),
+        "<p>Above</p><hr class=\"divider\"><p>Below</p>"
+    );
+
+    config.html.xhtml_style = true;
+    assert!(render_with_config("---", &config).contains("<hr class=\"divider\" />"));
+}
+
+#[test]
+fn test_drop_empty_paragraphs() {
+    use pulldown_cmark::{Event, Tag, TagEnd};
+
+    let events = || {
+        vec![
+            Event::Start(Tag::Paragraph),
+            Event::End(TagEnd::Paragraph),
+            Event::Start(Tag::Paragraph),
+            Event::Text(" ".into()),
+            Event::End(TagEnd::Paragraph),
+            Event::Start(Tag::Paragraph),
+            Event::Text("Content".into()),
+            Event::End(TagEnd::Paragraph),
+        ]
+    };
+    let render = |config: &HtmlConfig| {
+        let mut output = String::new();
+        let handler = DefaultHtmlWriter::new(&mut output, config.clone());
+        HtmlRenderer::new(handler)
+            .run(events().into_iter())
+            .unwrap();
+        output
+    };
+
+    let mut config = HtmlConfig::default();
+    config.html.pretty_print = false;
+    assert_eq!(render(&config), "<p></p><p> </p><p>Content</p>");
+
+    config.html.drop_empty_paragraphs = true;
+    assert_eq!(render(&config), "<p>Content</p>");
 }
 
 // Mixed configuration tests
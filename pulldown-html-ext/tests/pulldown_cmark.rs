@@ -330,6 +330,25 @@ fn test_trim_space_before_newline_at_paragraph_end() {
     assert_html_eq!(output, expected, markdown());
 }
 
+#[test]
+fn test_trim_trailing_whitespace_option() {
+    let mut config = HtmlConfig::default();
+    config.html.break_on_newline = false;
+    config.html.pretty_print = false;
+    config.html.trim_trailing_whitespace = true;
+
+    for original in [
+        "one\ntwo \t",
+        "one\ntwo \t\n",
+        "one\ntwo \n",
+        "one&#32;\ntwo&#9;",
+    ] {
+        let mut output = String::new();
+        push_html(&mut output, Parser::new(original), &config).unwrap();
+        assert_eq!(output, "<p>one\ntwo</p>");
+    }
+}
+
 #[test]
 fn test_trim_space_before_soft_break() {
     let original = "one \ntwo";
@@ -342,3 +361,27 @@ fn test_trim_space_before_soft_break() {
     push_html(&mut output, parser, &config).unwrap();
     assert_html_eq!(output, expected, markdown());
 }
+
+#[test]
+fn test_commonmark_strict_matches_reference() {
+    let mut config = HtmlConfig::default();
+    config.html.commonmark_strict = true;
+
+    for original in [
+        "one\ntwo  \nthree \\\nfour \t\n\n---\n\n# Heading\n",
+        "- tight\n- list\n\n1. loose\n\n2. list\n\n> quote\n",
+        "- item\n  - nested\n\n> - quoted\n>\n>   > deeper\n",
+        "![image](src.png \"title\")\n\n***\n\n<div>\nraw\n</div>\n",
+        "a & b < c\n",
+        "```\na < b && c\n```\n\n`a<b`\n",
+        "[a](https://example.com)\n",
+        "- [ ] task\n- [x] done\n",
+    ] {
+        let options = Options::ENABLE_TASKLISTS;
+        let mut expected = String::new();
+        pulldown_cmark::html::push_html(&mut expected, Parser::new_ext(original, options));
+        let mut output = String::new();
+        push_html(&mut output, Parser::new_ext(original, options), &config).unwrap();
+        assert_eq!(output, expected);
+    }
+}
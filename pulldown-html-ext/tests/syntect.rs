@@ -4,8 +4,10 @@ mod tests {
     use pulldown_html_ext::HtmlWriter;
     use pulldown_html_ext::SyntectWriter;
     use pulldown_html_ext::{
-        push_html_with_highlighting, HtmlConfig, SyntectConfig, SyntectConfigStyle,
+        push_html_with_highlighting, render_with_highlighting, CssMode, HtmlConfig, SyntectConfig,
+        SyntectConfigStyle,
     };
+    use std::sync::Arc;
     use syntect::highlighting::ThemeSet;
 
     use syntect::html::ClassStyle;
@@ -99,6 +101,175 @@ mod tests {
         assert!(html.contains("language-rust"));
     }
 
+    #[test]
+    fn test_css_link_mode() {
+        let config = HtmlConfig::with_syntect(SyntectConfig {
+            style: SyntectConfigStyle {
+                css_mode: CssMode::Link("/assets/highlight.css".to_string()),
+                ..SyntectConfigStyle::default()
+            },
+            ..Default::default()
+        });
+
+        let markdown = "```rust\nlet x = 42;\n```";
+        let output = render_with_highlighting(markdown, &config).unwrap();
+
+        assert!(output
+            .html()
+            .starts_with("<link rel=\"stylesheet\" href=\"/assets/highlight.css\">"));
+        assert!(!output.html().contains("<style>"));
+
+        let mut unused = String::new();
+        let writer = SyntectWriter::new(FmtWriter(&mut unused), &config);
+        assert_eq!(output.css(), Some(writer.get_theme_css().unwrap().as_str()));
+
+        // The convenience function keeps the link but drops the CSS
+        let html = push_html_with_highlighting(markdown, &config).unwrap();
+        assert_eq!(html, output.html());
+    }
+
+    #[test]
+    fn test_css_none_mode() {
+        let config = HtmlConfig::with_syntect(SyntectConfig {
+            style: SyntectConfigStyle {
+                css_mode: CssMode::None,
+                ..SyntectConfigStyle::default()
+            },
+            ..Default::default()
+        });
+
+        let output = render_with_highlighting("```rust\nlet x = 42;\n```", &config).unwrap();
+        assert!(!output.html().contains("<style>"));
+        assert!(!output.html().contains("<link"));
+        assert!(output.css().is_none());
+    }
+
+    #[test]
+    fn test_trim_blank_lines() {
+        let mut config = HtmlConfig::with_syntect(SyntectConfig::default());
+        config.elements.code_blocks.trim_blank_lines = true;
+
+        let html = push_html_with_highlighting("```rust\n\nlet x = 1;\n\n```", &config).unwrap();
+        assert!(html.contains("<code class=\"language-rust\"><span class=\"source rust\"><span"));
+        assert!(html.contains(";</span>\n</span></code>"));
+
+        config.elements.code_blocks.trim_blank_lines = false;
+        let html = push_html_with_highlighting("```rust\n\nlet x = 1;\n\n```", &config).unwrap();
+        assert!(html.contains("<span class=\"source rust\">\n<span"));
+    }
+
+    #[test]
+    fn test_code_text_transform_before_highlighting() {
+        let mut config = HtmlConfig::with_syntect(SyntectConfig::default());
+        config.hooks.code_text_transform = Some(Arc::new(|code: &str, lang: Option<&str>| {
+            assert_eq!(lang, Some("rust"));
+            code.replace("hunter2", "*******")
+        }));
+
+        let html =
+            push_html_with_highlighting("```rust\nlet pw = \"hunter2\";\n```", &config).unwrap();
+        assert!(!html.contains("hunter2"));
+        assert!(html.contains("*******"));
+    }
+
+    #[test]
+    fn test_theme_by_language() {
+        let mut config = HtmlConfig::with_syntect(SyntectConfig::default());
+        let style = config.syntect.as_mut().unwrap();
+        style
+            .theme_by_language
+            .insert("rust".to_string(), "base16-mocha.dark".to_string());
+        style
+            .theme_by_language
+            .insert("sh".to_string(), "InspiredGitHub".to_string());
+
+        let markdown = "```rust\nfn main() {}\n```\n```sh\necho hi\n```";
+        let output = render_with_highlighting(markdown, &config).unwrap();
+        let html = output.html();
+
+        // The global theme stays unscoped, the others apply within their language
+        let theme_set = ThemeSet::load_defaults();
+        let background = |name: &str| {
+            let color = theme_set.themes[name].settings.background.unwrap();
+            format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+        };
+        assert!(html.contains(".code {\n color"));
+        assert!(html.contains(".language-rust .code {"));
+        assert!(html.contains(".language-sh .code {"));
+        assert!(html.contains(&background("base16-ocean.dark")));
+        assert!(html.contains(&background("base16-mocha.dark")));
+        assert!(html.contains(&background("InspiredGitHub")));
+    }
+
+    #[test]
+    fn test_scope_class() {
+        let config = HtmlConfig::with_syntect(SyntectConfig {
+            style: SyntectConfigStyle {
+                scope_class: Some("hl".to_string()),
+                ..SyntectConfigStyle::default()
+            },
+            ..Default::default()
+        });
+
+        let html = push_html_with_highlighting("```rust\nlet x = 42;\n```", &config).unwrap();
+        assert!(html.contains("<div class=\"hl\"><pre><code class=\"language-rust\">"));
+        assert!(html.contains("</code></pre></div>"));
+        assert!(html.contains(".hl .code {"));
+        assert!(html.contains(".hl .keyword {"));
+        assert!(!html.contains("\n.keyword {"));
+    }
+
+    #[test]
+    fn test_line_numbers_from_startline() {
+        let mut config = HtmlConfig::with_syntect(SyntectConfig::default());
+        config.elements.code_blocks.line_numbers = true;
+
+        let html =
+            push_html_with_highlighting("```rust startline=42\nlet x = 1;\n```", &config).unwrap();
+        assert!(
+            html.contains("<code class=\"language-rust\"><span class=\"line-number\">42</span>")
+        );
+        assert!(!html.contains(">43<"));
+
+        // A start line near usize::MAX must not overflow when counting lines
+        let max = usize::MAX;
+        let html =
+            push_html_with_highlighting(&format!("```x startline={max}\na\nb\n```"), &config)
+                .unwrap();
+        assert_eq!(html.matches(&format!(">{max}</span>")).count(), 2);
+    }
+
+    #[test]
+    fn test_parses_without_extensions() {
+        let config = HtmlConfig::with_syntect(SyntectConfig::default());
+
+        // Heading attributes and front matter are left to the parser defaults
+        let html = push_html_with_highlighting("---\n# A {#x}", &config).unwrap();
+        assert!(html.contains("<hr>"));
+        assert!(html.contains("A {#x}</h1>"));
+        assert!(!html.contains("id=\"x\""));
+    }
+
+    #[test]
+    fn test_inline_language() {
+        let config = HtmlConfig::with_syntect(SyntectConfig {
+            style: SyntectConfigStyle {
+                inline_language: Some("rust".to_string()),
+                ..SyntectConfigStyle::default()
+            },
+            ..Default::default()
+        });
+
+        let html = push_html_with_highlighting("Call `fn main() {}` first", &config).unwrap();
+        assert!(html.contains("<code><span class=\"source rust\">"));
+        assert!(html.contains("<span class=\"storage type function rust\">fn</span>"));
+
+        // Without the option inline code is left alone
+        let config = HtmlConfig::with_syntect(SyntectConfig::default());
+        let html = push_html_with_highlighting("Call `fn main() {}` first", &config).unwrap();
+        assert!(html.contains("<code>fn main() {}</code>"));
+    }
+
     #[test]
     fn test_custom_class_style() {
         let config = HtmlConfig::with_syntect(SyntectConfig {
@@ -138,6 +309,22 @@ mod tests {
         assert!(html.contains("language-rust"));
     }
 
+    #[test]
+    fn test_language_alias_highlighting() {
+        let mut config = HtmlConfig::with_syntect(SyntectConfig::default());
+        config
+            .elements
+            .code_blocks
+            .language_aliases
+            .insert("rusty".to_string(), "rust".to_string());
+
+        let markdown = "```rusty\nfn main() {}\n```";
+        let html = push_html_with_highlighting(markdown, &config).unwrap();
+
+        assert!(html.contains("language-rust"));
+        assert!(html.contains("source rust"));
+    }
+
     #[test]
     fn test_unknown_language() {
         let config = HtmlConfig::with_syntect(SyntectConfig::default());